@@ -1,38 +1,132 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::Serialize;
 
 use crate::config::Config;
 use crate::exit_codes::ExitCode;
-use crate::output::Outcome;
-use crate::project_assets::get_imports;
+use crate::output::{Explanation, Outcome};
+use crate::project_assets::get_first_party;
+use crate::project_assets::get_lock_packages;
 use crate::project_assets::get_packages;
-use crate::project_assets::{get_dependencies, Dependency};
-use crate::project_assets::{get_site_packages, Package, PackageState};
+use crate::project_assets::{find_backports, get_target_python, is_stdlib_module, PythonVersion};
+use crate::project_assets::{get_config_usages, ConfigUsage};
+use crate::project_assets::{get_dependencies, Dependency, PackageName, VersionConflict};
+use crate::project_assets::{
+    get_imports, FileError, Import, ImportContext, ImportKind, MAIN_GROUP,
+};
+use crate::project_assets::{get_site_packages, Location, Package, PackageState, TestFiles};
 
 #[derive(Serialize, Debug, PartialEq, Eq, Clone)]
 pub struct AnalysisElement<'a> {
     pub package: &'a Package,
     pub dependency: Option<&'a Dependency>,
+    /// How the package is imported, or `None` if it is not.
+    pub import_kind: Option<ImportKind>,
+    /// When the package is needed, or `None` if it is not imported.
+    pub import_context: Option<ImportContext>,
+    /// Where the package is imported, sorted by location.
+    pub usages: Vec<&'a Import>,
+    /// Where the configuration of the project refers to the package, e.g. in a `Procfile`.
+    pub config_usages: Vec<&'a ConfigUsage>,
+}
+
+/// A dependency declared in a group that does not match where it is imported.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct Misplaced {
+    pub id: String,
+    pub group: String,
+    /// The first import that shows it.
+    pub location: Location,
 }
 
 struct ProjectAnalysis {
     packages: HashSet<Package>,
     dependencies: HashSet<Dependency>,
-    imports: HashSet<String>,
+    /// The imports of the project, per top-level module name.
+    imports: HashMap<String, Vec<Import>>,
+    /// The packages the configuration of the project refers to, without importing them.
+    config_usages: HashMap<PackageName, Vec<ConfigUsage>>,
 }
 
 impl ProjectAnalysis {
     fn new(
         packages: HashSet<Package>,
         dependencies: HashSet<Dependency>,
-        imports: HashSet<String>,
+        imports: HashSet<Import>,
     ) -> Self {
+        let mut by_name: HashMap<String, Vec<Import>> = HashMap::new();
+        for import in imports {
+            by_name
+                .entry(import.name().to_string())
+                .or_default()
+                .push(import);
+        }
         Self {
             packages,
             dependencies,
-            imports,
+            imports: by_name,
+            config_usages: HashMap::new(),
+        }
+    }
+
+    fn with_config_usages(mut self, config_usages: HashMap<PackageName, Vec<ConfigUsage>>) -> Self {
+        self.config_usages = config_usages;
+        self
+    }
+
+    /// Where the configuration of the project refers to the package.
+    fn config_usages(&self, pkg: &Package) -> Vec<&ConfigUsage> {
+        self.config_usages
+            .get(pkg.name())
+            .map_or_else(Vec::new, |usages| usages.iter().collect())
+    }
+
+    /// Whether the package is imported, or run or configured as a tool by the project.
+    fn is_used(&self, pkg: &Package) -> bool {
+        self.import_kind(pkg).is_some() || self.config_usages.contains_key(pkg.name())
+    }
+
+    /// The imports of the package, through any of its aliases.
+    fn usages(&self, pkg: &Package) -> Vec<&Import> {
+        let mut usages: Vec<&Import> = pkg
+            .aliases()
+            .iter()
+            .filter_map(|alias| self.imports.get(alias))
+            .flatten()
+            .collect();
+        usages.sort_by(|a, b| (a.location(), a.path()).cmp(&(b.location(), b.path())));
+        usages
+    }
+
+    /// The most certain way the package is imported, through any of its aliases.
+    fn import_kind(&self, pkg: &Package) -> Option<ImportKind> {
+        pkg.aliases()
+            .iter()
+            .filter_map(|alias| self.imports.get(alias))
+            .flatten()
+            .map(Import::kind)
+            .min()
+    }
+
+    /// The most needed context the package is imported in, through any of its aliases.
+    fn import_context(&self, pkg: &Package) -> Option<ImportContext> {
+        pkg.aliases()
+            .iter()
+            .filter_map(|alias| self.imports.get(alias))
+            .flatten()
+            .map(Import::context)
+            .min()
+    }
+
+    fn element<'a>(&'a self, pkg: &'a Package, dep: Option<&'a Dependency>) -> AnalysisElement<'a> {
+        AnalysisElement {
+            package: pkg,
+            dependency: dep,
+            import_kind: self.import_kind(pkg),
+            import_context: self.import_context(pkg),
+            usages: self.usages(pkg),
+            config_usages: self.config_usages(pkg),
         }
     }
 
@@ -42,11 +136,8 @@ impl ProjectAnalysis {
             .filter_map(|dep| {
                 self.packages
                     .iter()
-                    .find(|pkg| pkg.id() == dep.id() && !pkg.aliases().is_disjoint(&self.imports))
-                    .map(|pkg| AnalysisElement {
-                        package: pkg,
-                        dependency: Some(dep),
-                    })
+                    .find(|pkg| pkg.name() == dep.name() && self.is_used(pkg))
+                    .map(|pkg| self.element(pkg, Some(dep)))
             })
             .collect()
     }
@@ -54,9 +145,9 @@ impl ProjectAnalysis {
     fn get_unused(&self) -> Vec<AnalysisElement<'_>> {
         let used_packages = self.get_used();
 
-        let used_requirements: HashSet<String> = used_packages
+        let used_requirements: HashSet<&PackageName> = used_packages
             .iter()
-            .flat_map(|e| e.package.requirements().iter().cloned())
+            .flat_map(|e| e.package.requirements())
             .collect();
 
         self.dependencies
@@ -64,31 +155,22 @@ impl ProjectAnalysis {
             .filter_map(|dep| {
                 self.packages
                     .iter()
-                    .find(|pkg| pkg.id() == dep.id() && pkg.aliases().is_disjoint(&self.imports))
-                    .filter(|pkg| !used_requirements.contains(pkg.id()))
-                    .map(|pkg| AnalysisElement {
-                        package: pkg,
-                        dependency: Some(dep),
-                    })
+                    .find(|pkg| pkg.name() == dep.name() && !self.is_used(pkg))
+                    .filter(|pkg| !used_requirements.contains(pkg.name()))
+                    .map(|pkg| self.element(pkg, Some(dep)))
             })
             .collect()
     }
 
     fn get_untracked(&self) -> Vec<AnalysisElement<'_>> {
-        let dep_ids: HashSet<String> = self
-            .dependencies
-            .iter()
-            .map(|dep| dep.id().to_string())
-            .collect();
+        let dep_names: HashSet<&PackageName> =
+            self.dependencies.iter().map(Dependency::name).collect();
 
         self.packages
             .iter()
             .filter_map(|pkg| {
-                if !pkg.aliases().is_disjoint(&self.imports) && !dep_ids.contains(pkg.id()) {
-                    Some(AnalysisElement {
-                        package: pkg,
-                        dependency: None,
-                    })
+                if self.import_kind(pkg).is_some() && !dep_names.contains(pkg.name()) {
+                    Some(self.element(pkg, None))
                 } else {
                     None
                 }
@@ -96,38 +178,289 @@ impl ProjectAnalysis {
             .collect()
     }
 
-    fn scan(&self, config: &Config) -> Vec<AnalysisElement> {
-        match config.package_state {
+    /// The runtime dependencies that are only imported for type checking, which belong in a
+    /// development group instead.
+    fn get_type_only(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .get_used()
+            .iter()
+            .filter(|e| e.import_context == Some(ImportContext::TypeOnly))
+            .filter(|e| e.dependency.is_some_and(|dep| dep.group() == MAIN_GROUP))
+            .map(|e| e.package.id().to_string())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// The imported packages the project runs without, declared or not.
+    fn get_optional(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .get_used()
+            .into_iter()
+            .chain(self.get_untracked())
+            .filter(|e| e.import_context == Some(ImportContext::Optional))
+            .map(|e| e.package.id().to_string())
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// The dependencies only declared in dev or test groups, but imported by production code.
+    /// Extras are runtime dependencies the users opt into, so they are not dev ones. Imports for
+    /// type checking, or guarded by `except ImportError`, are left out, as the code runs without
+    /// them.
+    fn get_dev_in_production(&self, tests: &TestFiles) -> Vec<Misplaced> {
+        let runtime_names: HashSet<&PackageName> = self
+            .dependencies
+            .iter()
+            .filter(|dep| dep.group() == MAIN_GROUP || dep.is_optional())
+            .map(Dependency::name)
+            .collect();
+
+        let mut misplaced: Vec<Misplaced> = self
+            .get_used()
+            .iter()
+            .filter_map(|e| {
+                let dep = e
+                    .dependency
+                    .filter(|dep| !runtime_names.contains(dep.name()))?;
+                let import = e.usages.iter().find(|import| {
+                    import.context() == ImportContext::Runtime
+                        && !tests.contains(import.location().file())
+                })?;
+                Some(Misplaced {
+                    id: e.package.id().to_string(),
+                    group: dep.group().to_string(),
+                    location: import.location().clone(),
+                })
+            })
+            .collect();
+        misplaced.sort_by(|a, b| (&a.id, &a.group).cmp(&(&b.id, &b.group)));
+        misplaced
+    }
+
+    /// The dependencies of the main group that are only imported by tests.
+    /// The ones the configuration refers to, e.g. `gunicorn` in a `Procfile`, are run in
+    /// production.
+    fn get_test_only(&self, tests: &TestFiles) -> Vec<Misplaced> {
+        let mut misplaced: Vec<Misplaced> = self
+            .get_used()
+            .iter()
+            .filter(|e| e.dependency.is_some_and(|dep| dep.group() == MAIN_GROUP))
+            .filter(|e| e.config_usages.is_empty())
+            .filter(|e| {
+                e.usages
+                    .iter()
+                    .all(|import| tests.contains(import.location().file()))
+            })
+            .filter_map(|e| {
+                let import = e.usages.first()?;
+                Some(Misplaced {
+                    id: e.package.id().to_string(),
+                    group: MAIN_GROUP.to_string(),
+                    location: import.location().clone(),
+                })
+            })
+            .collect();
+        misplaced.sort_by(|a, b| a.id.cmp(&b.id));
+        misplaced
+    }
+
+    fn elements(&self, state: PackageState, groups: &[String]) -> Vec<AnalysisElement<'_>> {
+        let elements = match state {
             PackageState::Unused => self.get_unused(),
             PackageState::Untracked => self.get_untracked(),
             PackageState::Used => self.get_used(),
+        };
+        filter_groups(elements, groups)
+    }
+
+    fn scan(&self, config: &Config) -> Vec<AnalysisElement<'_>> {
+        self.elements(config.package_state, &config.groups)
+    }
+
+    /// Gathers what decides the state of one package, or `None` if it is neither declared nor
+    /// installed.
+    fn explain(&self, name: &PackageName, groups: &[String]) -> Option<Explanation<'_>> {
+        let package = self.packages.iter().find(|pkg| pkg.name() == name);
+        let mut declarations: Vec<&Dependency> = self
+            .dependencies
+            .iter()
+            .filter(|dep| dep.name() == name)
+            .collect();
+        if package.is_none() && declarations.is_empty() {
+            return None;
         }
+        declarations.sort_by(|a, b| a.group().cmp(b.group()));
+
+        // The requirements of used packages are what keeps a package out of the unused list.
+        let mut required_by: Vec<&PackageName> = self
+            .get_used()
+            .iter()
+            .map(|e| e.package)
+            .filter(|pkg| pkg.requirements().contains(name))
+            .map(Package::name)
+            .collect();
+        required_by.sort();
+        required_by.dedup();
+
+        let state = [
+            PackageState::Used,
+            PackageState::Unused,
+            PackageState::Untracked,
+        ]
+        .into_iter()
+        .find(|state| {
+            self.elements(*state, groups)
+                .iter()
+                .any(|e| e.package.name() == name)
+        });
+
+        Some(Explanation {
+            id: name.clone(),
+            state,
+            declarations,
+            package,
+            usages: package.map_or_else(Vec::new, |pkg| self.usages(pkg)),
+            config_usages: package.map_or_else(Vec::new, |pkg| self.config_usages(pkg)),
+            required_by,
+        })
     }
 }
 
-pub fn scan(config: Config) -> Result<ExitCode> {
-    let mut outcome = Outcome::default();
-    let imports = get_imports(&config).context("Failed to get imports from the project.")?;
+/// Keep only the elements whose dependency is in one of the selected groups.
+/// Untracked packages have no declaration, so they are never filtered out.
+fn filter_groups<'a>(
+    elements: Vec<AnalysisElement<'a>>,
+    groups: &[String],
+) -> Vec<AnalysisElement<'a>> {
+    if groups.is_empty() {
+        return elements;
+    }
+    elements
+        .into_iter()
+        .filter(|e| {
+            e.dependency
+                .map_or(true, |dep| groups.iter().any(|group| group == dep.group()))
+        })
+        .collect()
+}
 
-    let dependencies = get_dependencies(&config)
-        .context("Failed to get dependencies from the dependency specification file.")?;
+/// What is learned about the project while loading it, besides the analysis itself.
+struct Findings {
+    /// The files whose imports are missing.
+    errors: Vec<FileError>,
+    conflicts: Vec<VersionConflict>,
+    first_party: Vec<String>,
+    stdlib: Vec<String>,
+    python_version: Option<PythonVersion>,
+}
 
-    let site_packages = get_site_packages().context("Failed to get site packages.")?;
-    let packages = get_packages(site_packages).context("Failed to get packages.")?;
+/// Collects the imports, declared dependencies and packages of the project.
+fn load(config: &Config) -> Result<(ProjectAnalysis, Findings)> {
+    let (mut imports, errors) =
+        get_imports(config).context("Failed to get imports from the project.")?;
 
-    let analysis = ProjectAnalysis::new(packages, dependencies, imports);
+    // Modules of the project itself and of the standard library are never provided by an
+    // installed package.
+    let first_party =
+        get_first_party(config).context("Failed to get the modules of the project.")?;
+    let python_version = get_target_python(&config.dep_spec_files);
+    let mut stdlib = HashSet::new();
+    imports.retain(|import| {
+        if first_party.contains(import.name()) {
+            return false;
+        }
+        if is_stdlib_module(import.name(), python_version) {
+            stdlib.insert(import.name().to_string());
+            return false;
+        }
+        true
+    });
+
+    let (dependencies, conflicts) = get_dependencies(config)
+        .context("Failed to get dependencies from the dependency specification files.")?;
+
+    let packages = match &config.lock_file {
+        Some(lock_file) => get_lock_packages(lock_file)
+            .with_context(|| format!("Failed to get packages from {:?}.", lock_file))?,
+        None => {
+            let site_packages = get_site_packages().context("Failed to get site packages.")?;
+            get_packages(site_packages).context("Failed to get packages.")?
+        }
+    };
+
+    let mut first_party: Vec<String> = first_party.into_iter().collect();
+    first_party.sort();
+    let mut stdlib: Vec<String> = stdlib.into_iter().collect();
+    stdlib.sort();
+
+    // Tools and servers are often only run, e.g. from a `Makefile` or a `Procfile`.
+    let config_usages = get_config_usages(config, &packages)
+        .context("Failed to get the packages used by the configuration of the project.")?;
+
+    let analysis =
+        ProjectAnalysis::new(packages, dependencies, imports).with_config_usages(config_usages);
+    let findings = Findings {
+        errors,
+        conflicts,
+        first_party,
+        stdlib,
+        python_version,
+    };
+    Ok((analysis, findings))
+}
+
+pub fn scan(config: Config) -> Result<ExitCode> {
+    let mut outcome = Outcome::default();
+    let (analysis, findings) = load(&config)?;
     let elements = analysis.scan(&config);
 
     outcome.elements = elements;
+    outcome.groups = outcome.group_ids();
+    outcome.errors = findings.errors;
+    outcome.conflicts = findings.conflicts;
+    outcome.first_party = findings.first_party;
+    outcome.stdlib = findings.stdlib;
+    outcome.python_version = findings.python_version;
+    outcome.backports = findings
+        .python_version
+        .map_or_else(Vec::new, |python_version| {
+            find_backports(&analysis.dependencies, python_version)
+        });
+    outcome.type_only = analysis.get_type_only();
+    outcome.optional = analysis.get_optional();
+    let tests = TestFiles::new(&config.base_directory, &config.test_globs)?;
+    outcome.dev_in_production = analysis.get_dev_in_production(&tests);
+    outcome.test_only = analysis.get_test_only(&tests);
     outcome.success = outcome.elements.is_empty();
 
-    outcome.print_report(&config, std::io::stdout())
+    let exit_code = outcome.print_report(&config, std::io::stdout())?;
+
+    // A file that cannot be parsed may hide the imports of packages reported as unused.
+    if config.strict && !outcome.errors.is_empty() {
+        return Ok(ExitCode::GeneralError);
+    }
+    Ok(exit_code)
+}
+
+/// Explains why one package is reported as used, unused or untracked.
+pub fn why(config: Config, name: &PackageName) -> Result<ExitCode> {
+    let (analysis, _) = load(&config)?;
+
+    let Some(explanation) = analysis.explain(name, &config.groups) else {
+        bail!("`{}` is neither declared nor installed.", name);
+    };
+
+    explanation.print_report(&config, std::io::stdout())
 }
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::project_assets::{DependencyBuilder, PackageBuilder};
+    use crate::project_assets::{DependencyBuilder, Location, PackageBuilder};
+    use std::path::PathBuf;
 
     /// Helper function to create a Package instance.
     fn create_package(id: &str, aliases: &[&str], requirements: HashSet<String>) -> Package {
@@ -135,6 +468,19 @@ mod tests {
         PackageBuilder::new(id.to_string(), aliases, 0, requirements).build()
     }
 
+    // Helper function to create the location of an import.
+    fn test_location() -> Location {
+        Location::new(PathBuf::from("main.py"), None, 1, 1)
+    }
+
+    // Helper function to create statically imported module names.
+    fn create_imports(names: &[&str]) -> HashSet<Import> {
+        names
+            .iter()
+            .map(|name| Import::new(name.to_string(), ImportKind::Static, test_location()))
+            .collect()
+    }
+
     // Helper function to create a Dependency instance.
     fn create_dependency(id: &str) -> Dependency {
         DependencyBuilder::new(id.to_string())
@@ -142,6 +488,14 @@ mod tests {
             .build()
     }
 
+    // Helper function to create a Dependency instance in a group.
+    fn create_grouped_dependency(id: &str, group: &str) -> Dependency {
+        DependencyBuilder::new(id.to_string())
+            .version("1.0.0".to_string())
+            .group(group.to_string())
+            .build()
+    }
+
     #[test]
     fn test_get_used() {
         // let pkg_requirements = HashSet::from(["requirement1".to_string()]);
@@ -152,7 +506,7 @@ mod tests {
             HashSet::from(["requirement1".to_string()]),
         );
         let dep1 = create_dependency("pkg1");
-        let imports = HashSet::from(["alias1".to_string()]);
+        let imports = create_imports(&["alias1"]);
 
         let analysis = ProjectAnalysis::new(
             // config,
@@ -174,7 +528,7 @@ mod tests {
             &["alias1"],
             HashSet::from(["requirement1".to_string()]),
         );
-        let imports = HashSet::from(["alias1".to_string()]);
+        let imports = create_imports(&["alias1"]);
 
         let analysis = ProjectAnalysis::new(HashSet::from([pkg1]), HashSet::new(), imports);
 
@@ -213,7 +567,7 @@ mod tests {
         );
         let dep1 = create_dependency("pkg1");
         let dep2 = create_dependency("pkg2");
-        let imports = HashSet::from(["alias1".to_string(), "alias3".to_string()]);
+        let imports = create_imports(&["alias1", "alias3"]);
 
         let analysis = ProjectAnalysis::new(
             HashSet::from([pkg1, pkg2]),
@@ -257,7 +611,7 @@ mod tests {
         );
         let dep1 = create_dependency("pkg1");
         let dep2 = create_dependency("pkg2");
-        let imports = HashSet::from(["alias1".to_string()]);
+        let imports = create_imports(&["alias1"]);
         let analysis = ProjectAnalysis::new(
             HashSet::from([pkg1, pkg2.clone()]),
             HashSet::from([dep1.clone(), dep2.clone()]),
@@ -292,7 +646,7 @@ mod tests {
             &["alias1"],
             HashSet::from(["requirement1".to_string()]),
         );
-        let imports = HashSet::from(["alias1".to_string()]);
+        let imports = create_imports(&["alias1"]);
 
         let analysis = ProjectAnalysis::new(
             HashSet::from([pkg1]),
@@ -313,7 +667,7 @@ mod tests {
             &["alias1"],
             HashSet::from(["requirement1".to_string()]),
         );
-        let imports = HashSet::from(["unrelated_alias".to_string()]);
+        let imports = create_imports(&["unrelated_alias"]);
 
         let analysis = ProjectAnalysis::new(HashSet::from([pkg1]), HashSet::new(), imports);
 
@@ -337,7 +691,7 @@ mod tests {
             HashSet::from(["requirement1".to_string()]),
         ); // This package does not have a corresponding dependency.
         let dep1 = create_dependency("pkg1");
-        let imports = HashSet::from(["alias2".to_string()]);
+        let imports = create_imports(&["alias2"]);
 
         let analysis =
             ProjectAnalysis::new(HashSet::from([pkg1, pkg2]), HashSet::from([dep1]), imports);
@@ -357,13 +711,93 @@ mod tests {
             &["Alias1"],
             HashSet::from(["requirement1".to_string()]),
         );
-        let dep1 = create_dependency("pkg1"); // Different case from the package ID.
-        let imports = HashSet::from(["alias1".to_string()]); // Different case from the alias.
-
-        let analysis = ProjectAnalysis::new(HashSet::from([pkg1]), HashSet::from([dep1]), imports);
+        let dep1 = create_dependency("pkg1"); // Distribution names are case-insensitive.
 
+        // The import name matches the alias exactly, so the package is used.
+        let analysis = ProjectAnalysis::new(
+            HashSet::from([pkg1.clone()]),
+            HashSet::from([dep1.clone()]),
+            create_imports(&["Alias1"]),
+        );
         let used = analysis.get_used();
-        assert!(used.is_empty(), "Case differences should prevent matching");
+        assert_eq!(used.len(), 1, "The package id case should not matter");
+        assert_eq!(used[0].package.id(), "pkg1");
+
+        // Module names are case-sensitive, so a different alias case does not match.
+        let analysis = ProjectAnalysis::new(
+            HashSet::from([pkg1]),
+            HashSet::from([dep1]),
+            create_imports(&["alias1"]),
+        );
+        assert!(
+            analysis.get_used().is_empty(),
+            "The alias case should prevent matching"
+        );
+    }
+
+    #[test]
+    fn test_name_normalization() {
+        let pkg1 = create_package("Typing_Extensions", &["typing_extensions"], HashSet::new());
+        let pkg2 = create_package(
+            "pkg2",
+            &["alias2"],
+            HashSet::from(["Ruamel.YAML".to_string()]),
+        );
+        let pkg3 = create_package("ruamel_yaml", &["ruamel"], HashSet::new());
+        let dep1 = create_dependency("typing.extensions");
+        let dep2 = create_dependency("pkg2");
+        let dep3 = create_dependency("Ruamel-YAML");
+        let imports = create_imports(&["typing_extensions", "alias2"]);
+
+        let analysis = ProjectAnalysis::new(
+            HashSet::from([pkg1, pkg2, pkg3]),
+            HashSet::from([dep1, dep2, dep3]),
+            imports,
+        );
+
+        // Spellings of the same distribution name match once normalized.
+        let mut used: Vec<&str> = analysis.get_used().iter().map(|e| e.package.id()).collect();
+        used.sort();
+        assert_eq!(used, vec!["pkg2", "typing-extensions"]);
+        assert!(analysis.get_untracked().is_empty());
+
+        // ruamel-yaml is required by a used package, under a different spelling.
+        assert!(analysis.get_unused().is_empty());
+    }
+
+    #[test]
+    fn test_dynamic_imports() {
+        let pkg1 = create_package("redis", &["redis"], HashSet::new());
+        let pkg2 = create_package("ujson", &["ujson"], HashSet::new());
+        let dep1 = create_dependency("redis");
+        let dep2 = create_dependency("ujson");
+        let imports = HashSet::from([
+            Import::new("redis".to_string(), ImportKind::Dynamic, test_location()),
+            Import::new("ujson".to_string(), ImportKind::Dynamic, test_location()),
+            Import::new("ujson".to_string(), ImportKind::Static, test_location()),
+        ]);
+
+        let analysis = ProjectAnalysis::new(
+            HashSet::from([pkg1, pkg2]),
+            HashSet::from([dep1, dep2]),
+            imports,
+        );
+
+        // Dynamically imported packages are used, and a static import takes precedence.
+        let mut used: Vec<(&str, Option<ImportKind>)> = analysis
+            .get_used()
+            .iter()
+            .map(|e| (e.package.id(), e.import_kind))
+            .collect();
+        used.sort();
+        assert_eq!(
+            used,
+            vec![
+                ("redis", Some(ImportKind::Dynamic)),
+                ("ujson", Some(ImportKind::Static))
+            ]
+        );
+        assert!(analysis.get_unused().is_empty());
     }
 
     #[test]
@@ -380,7 +814,7 @@ mod tests {
         );
         let dep1 = create_dependency("pkg1");
         let dep2 = create_dependency("pkg2");
-        let imports = HashSet::from(["alias2".to_string()]);
+        let imports = create_imports(&["alias2"]);
 
         let analysis = ProjectAnalysis::new(
             HashSet::from([pkg1, pkg2]),
@@ -395,4 +829,264 @@ mod tests {
             "Both pkg1 and pkg2 should be considered used as alias2 is imported by both."
         );
     }
+
+    #[test]
+    fn test_filter_groups() {
+        let pkg1 = create_package("pkg1", &["alias1"], HashSet::new());
+        let pkg2 = create_package("pkg2", &["alias2"], HashSet::new());
+        let pkg3 = create_package("pkg3", &["alias3"], HashSet::new());
+        let dep1 = create_dependency("pkg1");
+        let dep2 = create_grouped_dependency("pkg2", "dev");
+
+        let analysis = ProjectAnalysis::new(
+            HashSet::from([pkg1, pkg2, pkg3]),
+            HashSet::from([dep1, dep2]),
+            HashSet::new(),
+        );
+
+        let unused = filter_groups(analysis.get_unused(), &[]);
+        assert_eq!(unused.len(), 2, "All groups are kept without a filter");
+
+        let unused = filter_groups(analysis.get_unused(), &["dev".to_string()]);
+        assert_eq!(unused.len(), 1);
+        assert_eq!(unused[0].package.id(), "pkg2");
+        assert_eq!(unused[0].dependency.map(|d| d.group()), Some("dev"));
+
+        // Untracked packages have no group, so they are always kept.
+        let analysis = ProjectAnalysis::new(
+            analysis.packages,
+            HashSet::new(),
+            create_imports(&["alias3"]),
+        );
+        let untracked = filter_groups(analysis.get_untracked(), &["dev".to_string()]);
+        assert_eq!(untracked.len(), 1);
+        assert_eq!(untracked[0].package.id(), "pkg3");
+    }
+
+    #[test]
+    fn test_usages() {
+        let pkg1 = create_package("pyyaml", &["yaml"], HashSet::new());
+        let pkg2 = create_package("requests", &["requests"], HashSet::new());
+        let dep1 = create_dependency("pyyaml");
+        let location = |file: &str, line| Location::new(PathBuf::from(file), None, line, 1);
+        let imports = HashSet::from([
+            Import::new("yaml".to_string(), ImportKind::Static, location("b.py", 1)),
+            Import::new(
+                "yaml.safe_load".to_string(),
+                ImportKind::Static,
+                location("a.py", 3),
+            ),
+            Import::new(
+                "requests".to_string(),
+                ImportKind::Static,
+                location("a.py", 1),
+            ),
+        ]);
+
+        let analysis =
+            ProjectAnalysis::new(HashSet::from([pkg1, pkg2]), HashSet::from([dep1]), imports);
+
+        let used = analysis.get_used();
+        assert_eq!(used.len(), 1);
+        let usages: Vec<(&str, &Location)> = used[0]
+            .usages
+            .iter()
+            .map(|import| (import.path(), import.location()))
+            .collect();
+        assert_eq!(
+            usages,
+            vec![
+                ("yaml.safe_load", &location("a.py", 3)),
+                ("yaml", &location("b.py", 1))
+            ]
+        );
+
+        let untracked = analysis.get_untracked();
+        assert_eq!(untracked.len(), 1);
+        assert_eq!(untracked[0].usages.len(), 1);
+        assert!(analysis.get_unused().is_empty());
+    }
+
+    #[test]
+    fn test_explain() {
+        let pkg1 = create_package(
+            "requests",
+            &["requests"],
+            HashSet::from(["certifi".to_string()]),
+        );
+        let pkg2 = create_package("certifi", &["certifi"], HashSet::new());
+        let pkg3 = create_package("pyyaml", &["yaml"], HashSet::new());
+        let dep1 = create_dependency("requests");
+        let dep2 = create_dependency("certifi");
+        let dep3 = create_grouped_dependency("certifi", "dev");
+        let dep4 = create_dependency("pyyaml");
+        let imports = create_imports(&["requests"]);
+
+        let analysis = ProjectAnalysis::new(
+            HashSet::from([pkg1, pkg2, pkg3]),
+            HashSet::from([dep1, dep2, dep3, dep4]),
+            imports,
+        );
+
+        let requests = analysis
+            .explain(&PackageName::new("requests"), &[])
+            .unwrap();
+        assert_eq!(requests.state, Some(PackageState::Used));
+        assert_eq!(requests.usages.len(), 1);
+        assert!(requests.required_by.is_empty());
+
+        // Certifi is not imported, but requests keeps it out of the unused list.
+        let certifi = analysis.explain(&PackageName::new("certifi"), &[]).unwrap();
+        assert_eq!(certifi.state, None);
+        let groups: Vec<&str> = certifi.declarations.iter().map(|dep| dep.group()).collect();
+        assert_eq!(groups, vec!["dev", "main"]);
+        assert!(certifi.usages.is_empty());
+        assert_eq!(certifi.required_by, vec![&PackageName::new("requests")]);
+
+        let pyyaml = analysis.explain(&PackageName::new("PyYAML"), &[]).unwrap();
+        assert_eq!(pyyaml.state, Some(PackageState::Unused));
+        let dev = ["dev".to_string()];
+        assert_eq!(
+            analysis
+                .explain(&PackageName::new("pyyaml"), &dev)
+                .unwrap()
+                .state,
+            None
+        );
+
+        assert!(analysis.explain(&PackageName::new("numpy"), &[]).is_none());
+    }
+
+    #[test]
+    fn test_import_contexts() {
+        let pkg1 = create_package("pandas", &["pandas"], HashSet::new());
+        let pkg2 = create_package("mypy-boto3-s3", &["mypy_boto3_s3"], HashSet::new());
+        let pkg3 = create_package("ujson", &["ujson"], HashSet::new());
+        let pkg4 = create_package("orjson", &["orjson"], HashSet::new());
+        let dep1 = create_dependency("pandas");
+        let dep2 = create_dependency("mypy-boto3-s3");
+        let dep3 = create_grouped_dependency("pandas-stubs", "typing");
+        let dep4 = create_dependency("ujson");
+        let import = |name: &str, context| {
+            Import::new(name.to_string(), ImportKind::Static, test_location()).with_context(context)
+        };
+        let imports = HashSet::from([
+            import("pandas", ImportContext::TypeOnly),
+            import("pandas", ImportContext::Runtime),
+            import("mypy_boto3_s3", ImportContext::TypeOnly),
+            import("ujson", ImportContext::Optional),
+            import("orjson", ImportContext::Optional),
+        ]);
+
+        let analysis = ProjectAnalysis::new(
+            HashSet::from([pkg1, pkg2, pkg3, pkg4]),
+            HashSet::from([dep1, dep2, dep3, dep4]),
+            imports,
+        );
+
+        // A runtime import anywhere makes the package a runtime one.
+        assert_eq!(analysis.get_type_only(), vec!["mypy-boto3-s3"]);
+        assert_eq!(analysis.get_optional(), vec!["orjson", "ujson"]);
+    }
+
+    #[test]
+    fn test_config_usages() {
+        let pkg1 = create_package("gunicorn", &["gunicorn"], HashSet::new());
+        let pkg2 = create_package("black", &["black"], HashSet::new());
+        let dep1 = create_dependency("gunicorn");
+        let dep2 = create_dependency("black");
+        let usage = ConfigUsage::new(PathBuf::from("Procfile"), 1, "gunicorn".to_string());
+
+        let analysis = ProjectAnalysis::new(
+            HashSet::from([pkg1, pkg2]),
+            HashSet::from([dep1, dep2]),
+            HashSet::new(),
+        )
+        .with_config_usages(HashMap::from([(PackageName::new("gunicorn"), vec![usage])]));
+
+        // A package run by the project is used, even though it is never imported.
+        let used = analysis.get_used();
+        assert_eq!(used.len(), 1);
+        assert_eq!(used[0].package.id(), "gunicorn");
+        assert!(used[0].usages.is_empty());
+        assert_eq!(used[0].config_usages.len(), 1);
+        let unused: Vec<&str> = analysis
+            .get_unused()
+            .iter()
+            .map(|e| e.package.id())
+            .collect();
+        assert_eq!(unused, vec!["black"]);
+
+        let gunicorn = analysis
+            .explain(&PackageName::new("gunicorn"), &[])
+            .unwrap();
+        assert_eq!(gunicorn.state, Some(PackageState::Used));
+        assert_eq!(gunicorn.config_usages[0].file(), &PathBuf::from("Procfile"));
+    }
+
+    #[test]
+    fn test_misplaced_dependencies() {
+        let pkg1 = create_package("pytest", &["pytest"], HashSet::new());
+        let pkg2 = create_package("freezegun", &["freezegun"], HashSet::new());
+        let pkg3 = create_package("requests", &["requests"], HashSet::new());
+        let pkg4 = create_package("types-requests", &["requests_stubs"], HashSet::new());
+        let pkg5 = create_package("redis", &["redis"], HashSet::new());
+        let pkg6 = create_package("ujson", &["ujson"], HashSet::new());
+        let dep1 = create_dependency("pytest");
+        let dep2 = create_grouped_dependency("freezegun", "dev");
+        let dep3 = create_dependency("requests");
+        let dep4 = create_grouped_dependency("types-requests", "typing");
+        // An extra is a runtime dependency, even when imported by production code.
+        let dep5 = DependencyBuilder::new("redis".to_string())
+            .group("cache".to_string())
+            .optional()
+            .build();
+        let dep6 = create_grouped_dependency("ujson", "dev");
+        let pkg7 = create_package("gunicorn", &["gunicorn"], HashSet::new());
+        let dep7 = create_dependency("gunicorn");
+        let import = |name: &str, file: &str| {
+            Import::new(
+                name.to_string(),
+                ImportKind::Static,
+                Location::new(PathBuf::from(file), None, 1, 1),
+            )
+        };
+        let imports = HashSet::from([
+            import("pytest", "tests/test_app.py"),
+            import("pytest", "conftest.py"),
+            import("freezegun", "tests/test_app.py"),
+            import("freezegun", "app/clock.py"),
+            import("requests", "app/client.py"),
+            import("requests", "tests/test_app.py"),
+            import("requests_stubs", "app/client.py").with_context(ImportContext::TypeOnly),
+            import("redis", "app/cache.py"),
+            import("ujson", "app/client.py").with_context(ImportContext::Optional),
+            import("gunicorn", "tests/test_server.py"),
+        ]);
+        // Run in production by the `Procfile`, though only imported by tests.
+        let usage = ConfigUsage::new(PathBuf::from("Procfile"), 1, "gunicorn".to_string());
+
+        let analysis = ProjectAnalysis::new(
+            HashSet::from([pkg1, pkg2, pkg3, pkg4, pkg5, pkg6, pkg7]),
+            HashSet::from([dep1, dep2, dep3, dep4, dep5, dep6, dep7]),
+            imports,
+        )
+        .with_config_usages(HashMap::from([(PackageName::new("gunicorn"), vec![usage])]));
+        let globs = ["tests/", "test_*.py", "conftest.py"].map(String::from);
+        let tests = TestFiles::new(&PathBuf::from("."), &globs).unwrap();
+
+        let dev_in_production = analysis.get_dev_in_production(&tests);
+        assert_eq!(dev_in_production.len(), 1);
+        assert_eq!(dev_in_production[0].id, "freezegun");
+        assert_eq!(dev_in_production[0].group, "dev");
+        assert_eq!(
+            dev_in_production[0].location.file(),
+            PathBuf::from("app/clock.py")
+        );
+
+        let test_only = analysis.get_test_only(&tests);
+        let ids: Vec<&str> = test_only.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["pytest"]);
+        assert_eq!(test_only[0].location.file(), PathBuf::from("conftest.py"));
+    }
 }
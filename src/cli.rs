@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 use std::path::PathBuf;
 
@@ -75,17 +75,119 @@ pub struct Opts {
     )]
     pub output: OutputKind,
 
-    /// Select the depencency specification file of choice if more than one exists.
-    /// By default, `pyproject.toml` is selected
+    /// Only read dependency specification files of the given type.
+    /// By default, every supported file at the root of the project is read
+    /// (`pyproject.toml`, `Pipfile`, `setup.cfg`, `setup.py`, `requirements*.txt`,
+    /// `*requirements.txt` and `requirements/*.txt`) and their declarations are merged.
     #[arg(
         long,
         short = 't',
         value_name("DEP_TYPE"),
-        default_value("poetry"),
+        help = "Only read dependency files of the given type (default: all)",
         value_enum,
         long_help
     )]
-    pub dep_type: DepType,
+    pub dep_type: Option<DepType>,
+
+    /// Restrict the analysis to the given dependency groups. Groups come from
+    /// `[tool.poetry.group.<name>]`, `[project.optional-dependencies]`, `[dependency-groups]`,
+    /// Pipfile categories and requirements file names (e.g. `requirements-dev.txt` is `dev`).
+    /// Runtime dependencies are in the `main` group. Can be given multiple times.
+    /// By default, all groups are analyzed.
+    #[arg(
+        long,
+        short = 'g',
+        value_name("GROUP"),
+        help = "Only analyze the given dependency group(s) (default: all)",
+        long_help
+    )]
+    pub group: Vec<String>,
+
+    /// Where to read the project's packages from. `env` reads the site-packages of the active
+    /// Python environment. `lock` reads `poetry.lock`, `uv.lock` or `pdm.lock` at the root of the
+    /// project instead, so no environment is needed; import names then come from a bundled
+    /// mapping of distribution names and package sizes are unknown.
+    #[arg(
+        long,
+        short = 'p',
+        value_name("SOURCE"),
+        default_value("env"),
+        help = "Where to read installed packages from",
+        value_enum,
+        long_help
+    )]
+    pub package_source: PackageSource,
+
+    /// Exit with an error when a Python file or notebook cannot be read or parsed. Such files are
+    /// always reported, but by default the analysis goes on without their imports, so packages
+    /// only they import may wrongly be reported as unused.
+    #[arg(long, help = "Fail when a file cannot be read or parsed", long_help)]
+    pub strict: bool,
+
+    /// Do not read or write the import cache. By default, the imports of each file are kept in
+    /// `.unpack_cache/` at the root of the project, so files that have not changed since the
+    /// previous run are not parsed again.
+    #[arg(long, help = "Do not use the import cache", long_help)]
+    pub no_cache: bool,
+
+    /// Remove the import cache at the root of the project before running, so every file is
+    /// parsed again.
+    #[arg(long, help = "Remove the import cache before running", long_help)]
+    pub clear_cache: bool,
+
+    /// Skip the files and directories matching a glob, e.g. `--exclude migrations/`. Globs are
+    /// matched like the lines of a `.gitignore`, from the root of the project. Can be given
+    /// multiple times, and adds to the `exclude` list of `[tool.unpack]` in `pyproject.toml`.
+    #[arg(
+        long,
+        value_name("GLOB"),
+        help = "Skip the files matching the glob(s)",
+        long_help
+    )]
+    pub exclude: Vec<String>,
+
+    /// Only scan the Python files matching a glob for imports, e.g. `--include 'src/**'`. Globs are
+    /// matched like the lines of a `.gitignore`, from the root of the project, and take precedence
+    /// over ignore files. Can be given multiple times, and adds to the `include` list of
+    /// `[tool.unpack]` in `pyproject.toml`.
+    #[arg(
+        long,
+        value_name("GLOB"),
+        help = "Only scan the files matching the glob(s)",
+        long_help
+    )]
+    pub include: Vec<String>,
+
+    /// Scan the files ignored by `.gitignore` files, which are skipped by default inside a git
+    /// repository. Same as `gitignore = false` in `[tool.unpack]`.
+    #[arg(long, help = "Do not respect .gitignore files", long_help)]
+    pub no_gitignore: bool,
+
+    /// Scan the files ignored by the global gitignore file of git, e.g. `~/.config/git/ignore`.
+    /// Same as `global-gitignore = false` in `[tool.unpack]`.
+    #[arg(long, help = "Do not respect the global gitignore file", long_help)]
+    pub no_global_gitignore: bool,
+
+    /// Scan the files ignored by `.ignore` files. Same as `ignore-files = false` in
+    /// `[tool.unpack]`.
+    #[arg(long, help = "Do not respect .ignore files", long_help)]
+    pub no_ignore_files: bool,
+
+    /// Mark the files matching a glob as tests, e.g. `--test-glob 'integration/'`. Globs are
+    /// matched like the lines of a `.gitignore`, from the root of the project. Dev dependencies
+    /// imported outside of tests, and runtime dependencies only imported by tests, are reported.
+    /// Can be given multiple times, and adds to the default globs, `tests/`, `test_*.py` and
+    /// `conftest.py`, and to the `test-globs` list of `[tool.unpack]` in `pyproject.toml`.
+    #[arg(
+        long,
+        value_name("GLOB"),
+        help = "Mark the files matching the glob(s) as tests",
+        long_help
+    )]
+    pub test_glob: Vec<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
 }
 
 impl Opts {
@@ -94,6 +196,17 @@ impl Opts {
     }
 }
 
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Explain why a package is reported as used, unused or untracked: the declarations that
+    /// match it, the import names it provides, the project files that import them and the used
+    /// packages that require it.
+    Why {
+        /// The name of the distribution, e.g. `PyYAML`
+        package: String,
+    },
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Env {
     #[allow(dead_code)]
@@ -111,10 +224,22 @@ pub enum OutputKind {
     Json,
 }
 
-#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DepType {
-    /// requirements.txt
+    /// requirements.txt and other requirements files
     Pip,
-    /// pyproject.toml
+    /// pyproject.toml (Poetry or PEP 621)
     Poetry,
+    /// Pipfile
+    Pipenv,
+    /// setup.cfg and setup.py
+    Setuptools,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackageSource {
+    /// The site-packages of the active Python environment
+    Env,
+    /// poetry.lock, uv.lock or pdm.lock
+    Lock,
 }
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use crate::cli::{DepType, Env, OutputKind};
+use crate::cli::{Env, OutputKind};
 use crate::project_assets::PackageState;
 
 pub struct Config {
@@ -11,13 +11,17 @@ pub struct Config {
     /// Ex: `Unused`, `Untracked`, `Used`
     pub package_state: PackageState,
 
-    /// The path to the dependency specification file.
-    /// Ex: `requirements.txt` or `pyproject.toml`
-    pub dep_spec_file: PathBuf,
+    /// The paths to the dependency specification files, whose declarations are merged.
+    /// Ex: `pyproject.toml` and `requirements-dev.txt`
+    pub dep_spec_files: Vec<PathBuf>,
 
-    /// The type of dependency file you would like to use for analysis (If more than one available)
-    /// Defaults to pyproject.toml
-    pub dep_type: DepType,
+    /// The lock file to read the project's packages from, instead of the site-packages.
+    /// Ex: `poetry.lock` or `uv.lock`
+    pub lock_file: Option<PathBuf>,
+
+    /// The dependency groups to analyze, or all of them if empty.
+    /// Ex: `main`, `dev`
+    pub groups: Vec<String>,
 
     /// Whether to ignore hidden files and directories (or not).
     pub ignore_hidden: bool,
@@ -28,7 +32,35 @@ pub struct Config {
     /// all files under subdirectories of the current directory, etc.
     pub max_depth: Option<usize>,
 
+    /// Globs of the only files to scan for imports, or all of them if empty.
+    /// Ex: `src/**`
+    pub include: Vec<String>,
+
+    /// Globs of the files and directories never to scan.
+    /// Ex: `migrations/` or `vendor/`
+    pub exclude: Vec<String>,
+
+    /// Whether to skip the files ignored by `.gitignore` files (or not).
+    pub gitignore: bool,
+
+    /// Whether to skip the files ignored by the global gitignore file of git (or not).
+    pub global_gitignore: bool,
+
+    /// Whether to skip the files ignored by `.ignore` files (or not).
+    pub ignore_files: bool,
+
+    /// Globs of the test files, whose imports do not make a package needed in production.
+    /// Ex: `tests/`, `test_*.py` or `conftest.py`
+    pub test_globs: Vec<String>,
+
+    /// Whether to fail when a file cannot be read or parsed, as its imports are then missing.
+    pub strict: bool,
+
+    /// Whether to reuse, and update, the imports cached in `.unpack_cache/` by previous runs.
+    pub cache: bool,
+
     /// The environment to run in.
+    #[allow(dead_code)]
     pub env: Env,
 
     /// The output format.
@@ -5,17 +5,37 @@ mod exit_codes;
 mod output;
 mod project_assets;
 
+use std::collections::HashSet;
 use std::env;
 use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, bail, Context, Result};
 use clap::Parser;
+use glob::glob;
 
-use crate::cli::{DepType, Env, Opts};
+use crate::cli::{Command, DepType, Env, Opts, PackageSource};
 use crate::config::Config;
 use crate::exit_codes::ExitCode;
+use crate::project_assets::{
+    clear_cache, get_constraint_files, get_tool_config, spec_file_type, PackageName,
+};
 
-const DEP_SPEC_FILES: [&str; 2] = ["requirements.txt", "pyproject.toml"];
+/// Dependency specification files with a fixed name, in the order their declarations are merged.
+const DEP_SPEC_FILES: [&str; 4] = ["pyproject.toml", "Pipfile", "setup.cfg", "setup.py"];
+
+/// Lock files packages can be read from, in order of preference.
+const LOCK_FILES: [&str; 3] = ["poetry.lock", "uv.lock", "pdm.lock"];
+
+/// Requirements files, e.g. `requirements.txt`, `requirements-dev.txt`, `test-requirements.txt`
+/// or `requirements/base.txt`.
+const REQUIREMENTS_FILE_PATTERNS: [&str; 3] = [
+    "requirements*.txt",
+    "*requirements.txt",
+    "requirements/*.txt",
+];
+
+/// Test files, unless other globs are given.
+const DEFAULT_TEST_GLOBS: [&str; 3] = ["tests/", "test_*.py", "conftest.py"];
 
 fn main() {
     let result = run();
@@ -32,68 +52,154 @@ fn main() {
 
 fn run() -> Result<ExitCode> {
     let opts = Opts::parse();
+    let command = opts.command.clone();
 
-    let config = construct_config(opts)?;
+    if opts.clear_cache {
+        clear_cache(&opts.base_directory)?;
+    }
+
+    let mut config = construct_config(opts)?;
 
     set_working_dir(&config)?;
+    // The project is read from the working directory from now on, e.g. `-b proj` is now `.`
+    config.base_directory = PathBuf::from(".");
 
-    analyze::scan(config)
+    match command {
+        Some(Command::Why { package }) => analyze::why(config, &PackageName::new(&package)),
+        None => analyze::scan(config),
+    }
 }
 
 fn construct_config(opts: Opts) -> Result<Config> {
     let base_directory = &opts.base_directory;
-    let dep_type = opts.dep_type;
-    let dep_files = get_dependency_spec_files(base_directory)?;
-    let dep_spec_file = match opts.dep_type {
-        DepType::Pip => dep_files
-            .iter()
-            .find(|file| file.ends_with("requirements.txt"))
-            .ok_or_else(|| anyhow!("Could not find `requirements.txt` in the provided directory."))?
-            .to_owned(),
-        DepType::Poetry => dep_files
-            .iter()
-            .find(|file| file.ends_with("pyproject.toml"))
-            .ok_or_else(|| anyhow!("Could not find `pyproject.toml` in the provided directory."))?
-            .to_owned(),
+    let dep_spec_files: Vec<PathBuf> = get_dependency_spec_files(base_directory)?
+        .into_iter()
+        .filter(|file| opts.dep_type.is_none() || spec_file_type(file) == opts.dep_type)
+        .collect();
+
+    if dep_spec_files.is_empty() {
+        let dep_type = opts.dep_type.expect("Files of any type are found");
+        bail!(
+            "Could not find a `{:?}` dependency specification file in the provided directory.",
+            dep_type
+        );
+    }
+
+    let lock_file = match opts.package_source {
+        PackageSource::Env => None,
+        PackageSource::Lock => Some(get_lock_file(base_directory)?),
     };
 
+    let max_depth = opts.max_depth();
+
+    // Command line options add to, or override, the `[tool.unpack]` table.
+    let tool_config = get_tool_config(base_directory)?;
+    let include = [tool_config.include, opts.include].concat();
+    let exclude = [tool_config.exclude, opts.exclude].concat();
+    let gitignore = !opts.no_gitignore && tool_config.gitignore.unwrap_or(true);
+    let global_gitignore =
+        !opts.no_global_gitignore && tool_config.global_gitignore.unwrap_or(true);
+    let ignore_files = !opts.no_ignore_files && tool_config.ignore_files.unwrap_or(true);
+    let test_globs = [
+        DEFAULT_TEST_GLOBS.map(String::from).to_vec(),
+        tool_config.test_globs,
+        opts.test_glob,
+    ]
+    .concat();
+
     let ignore_hidden = opts.ignore_hidden;
     let output = opts.output;
-    let max_depth = opts.max_depth();
     Ok(Config {
         base_directory: base_directory.to_owned(),
-        dep_spec_file,
-        dep_type,
+        dep_spec_files,
+        lock_file,
         ignore_hidden,
         max_depth,
+        include,
+        exclude,
+        gitignore,
+        global_gitignore,
+        ignore_files,
+        test_globs,
+        strict: opts.strict,
+        cache: !opts.no_cache,
         env: Env::Dev,
         output,
         package_state: opts.package_status,
+        groups: opts.group,
     })
 }
 
+/// Find every dependency specification file at the root of the project.
+/// The paths are relative to the root, as unpack runs from there.
 pub fn get_dependency_spec_files(base_dir: &Path) -> Result<Vec<PathBuf>> {
-    let mut files = Vec::new();
+    let mut files: Vec<PathBuf> = DEP_SPEC_FILES
+        .iter()
+        .map(PathBuf::from)
+        .filter(|file_name| base_dir.join(file_name).is_file())
+        .collect();
 
-    for dir in base_dir.ancestors() {
-        for file_name in DEP_SPEC_FILES.iter() {
-            let file_path = dir.join(file_name);
-            if file_path.exists() {
+    let base_pattern = glob::Pattern::escape(&base_dir.to_string_lossy());
+    for pattern in REQUIREMENTS_FILE_PATTERNS {
+        let requirements_files = glob(&format!("{}/{}", base_pattern, pattern))
+            .context("Invalid requirements file pattern")?
+            .filter_map(Result::ok)
+            .filter(|file_path| file_path.is_file());
+        for file_path in requirements_files {
+            let file_path = file_path
+                .strip_prefix(base_dir)
+                .map(Path::to_path_buf)
+                .unwrap_or(file_path);
+            if !files.contains(&file_path) {
                 files.push(file_path);
             }
         }
     }
 
+    // Constraint files only pin the packages other files declare, so they are read through the
+    // `-c` of those files rather than as requirements files, e.g. `requirements/constraints.txt`.
+    let is_requirements_file = |file: &PathBuf| spec_file_type(file) == Some(DepType::Pip);
+    let constraint_files: HashSet<PathBuf> = files
+        .iter()
+        .filter(|file| is_requirements_file(file))
+        .flat_map(|file| get_constraint_files(&base_dir.join(file)))
+        .filter_map(|file| file.canonicalize().ok())
+        .collect();
+    files.retain(|file| {
+        let is_constraints = file
+            .file_stem()
+            .is_some_and(|stem| stem.to_string_lossy().to_lowercase().contains("constraint"))
+            || base_dir
+                .join(file)
+                .canonicalize()
+                .is_ok_and(|file| constraint_files.contains(&file));
+        !(is_requirements_file(file) && is_constraints)
+    });
+
     if files.is_empty() {
         Err(anyhow!(format!(
-            "Could not find `Requirements.txt` or `pyproject.toml` in '{}' or any parent directory",
-            env::current_dir().unwrap().to_string_lossy()
+            "Could not find `pyproject.toml`, `Pipfile`, `setup.cfg`, `setup.py` or a requirements file in '{}'",
+            base_dir.to_string_lossy()
         )))
     } else {
         Ok(files)
     }
 }
 
+/// Find the lock file at the root of the project, relative to the root.
+fn get_lock_file(base_dir: &Path) -> Result<PathBuf> {
+    LOCK_FILES
+        .iter()
+        .map(PathBuf::from)
+        .find(|file_name| base_dir.join(file_name).is_file())
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not find `poetry.lock`, `uv.lock` or `pdm.lock` in '{}'",
+                base_dir.to_string_lossy()
+            )
+        })
+}
+
 fn set_working_dir(config: &Config) -> Result<()> {
     if !config.base_directory.exists() {
         bail!("The provided path does not exist.");
@@ -109,3 +215,42 @@ fn set_working_dir(config: &Config) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_constraint_files_are_not_requirements_files() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir(temp_dir.path().join("requirements")).unwrap();
+        fs::write(
+            temp_dir.path().join("requirements/base.txt"),
+            "-c constraints.txt\n--constraint=pins.txt\nrequests\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("requirements/constraints.txt"),
+            "requests==2.31.0\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("requirements/pins.txt"), "idna==3.6\n").unwrap();
+        fs::write(
+            temp_dir.path().join("requirements/dev.txt"),
+            "-r base.txt\npytest\n",
+        )
+        .unwrap();
+
+        let mut files = get_dependency_spec_files(temp_dir.path()).unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("requirements/base.txt"),
+                PathBuf::from("requirements/dev.txt")
+            ]
+        );
+    }
+}
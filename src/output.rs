@@ -1,5 +1,7 @@
 use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
 use std::io::Write;
+use std::path::Path;
 
 use anyhow::Result;
 use bytesize::ByteSize;
@@ -7,25 +9,244 @@ use serde::Serialize;
 
 use tabled::{settings::Style, Table, Tabled};
 
-use crate::analyze::AnalysisElement;
+use crate::analyze::{AnalysisElement, Misplaced};
 use crate::cli::OutputKind;
 use crate::config::Config;
 use crate::exit_codes::ExitCode;
+use crate::project_assets::{
+    Backport, ConfigUsage, Dependency, FileError, FileErrorKind, Import, ImportContext, ImportKind,
+    Location, Origin, Package, PackageName, PackageState, PythonVersion, VersionConflict,
+};
 
 #[derive(Default, Serialize, Debug, PartialEq, Eq, Clone)]
 pub struct Outcome<'a> {
     pub success: bool,
     pub elements: Vec<AnalysisElement<'a>>,
+    /// Files that could not be read or parsed, so their imports are missing.
+    pub errors: Vec<FileError>,
+    /// The ids of the packages in `elements`, per dependency group.
+    pub groups: BTreeMap<String, Vec<String>>,
+    /// Packages declared with different versions by different files.
+    pub conflicts: Vec<VersionConflict>,
+    /// The top-level modules that belong to the project itself.
+    pub first_party: Vec<String>,
+    /// The imported top-level modules of the standard library.
+    pub stdlib: Vec<String>,
+    /// The Python version the standard library modules are checked against, when known.
+    pub python_version: Option<PythonVersion>,
+    /// Declared backports of modules the target Python already provides.
+    pub backports: Vec<Backport>,
+    /// Runtime dependencies that are only imported for type checking.
+    pub type_only: Vec<String>,
+    /// Packages that are only imported optionally, in `try:` blocks handling `ImportError`.
+    pub optional: Vec<String>,
+    /// Dependencies outside of the main group that production code imports.
+    pub dev_in_production: Vec<Misplaced>,
+    /// Dependencies of the main group that only tests import.
+    pub test_only: Vec<Misplaced>,
 }
 
 #[derive(Tabled)]
 struct Record<'r> {
-    package: &'r str,
+    package: String,
     version: &'r str,
+    usages: usize,
     size: String,
 }
 
+/// Format an origin the way compilers report locations, e.g. `requirements.txt:3`
+fn format_origin(origin: &Origin) -> String {
+    match origin.line() {
+        Some(line) => format!("{}:{}", origin.file().display(), line),
+        None => origin.file().display().to_string(),
+    }
+}
+
+/// Format a location the way compilers report them, e.g. `app.py:3:8` or `notebook.ipynb[2]:1:1`
+fn format_location(location: &Location) -> String {
+    let cell = location
+        .cell()
+        .map_or_else(String::new, |cell| format!("[{}]", cell));
+    format!(
+        "{}{}:{}:{}",
+        location.file().display(),
+        cell,
+        location.line(),
+        location.column()
+    )
+}
+
+/// Notes on how a package is imported, e.g. ` (dynamic, optional)`, or nothing for static
+/// runtime imports.
+fn import_notes(kind: Option<ImportKind>, context: Option<ImportContext>) -> String {
+    let mut notes = Vec::new();
+    match kind {
+        Some(ImportKind::Dynamic) => notes.push("dynamic"),
+        Some(ImportKind::Recovered) => notes.push("recovered"),
+        Some(ImportKind::Static) | None => (),
+    }
+    match context {
+        Some(ImportContext::Optional) => notes.push("optional"),
+        Some(ImportContext::TypeOnly) => notes.push("type-only"),
+        Some(ImportContext::Runtime) | None => (),
+    }
+    if notes.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", notes.join(", "))
+    }
+}
+
+/// Notes on how a package of the results is used, e.g. ` (type-only)`, or ` (config)` when it is
+/// only referred to by the configuration of the project.
+fn usage_notes(element: &AnalysisElement) -> String {
+    if element.usages.is_empty() && !element.config_usages.is_empty() {
+        " (config)".to_string()
+    } else {
+        import_notes(element.import_kind, element.import_context)
+    }
+}
+
+/// What decides the state of one package, as shown by `unpack why`.
+#[derive(Serialize, Debug)]
+pub struct Explanation<'a> {
+    pub id: PackageName,
+    /// The state the package is reported in, or `None` if it is in none of them.
+    pub state: Option<PackageState>,
+    /// The declarations of the package, one per dependency group.
+    pub declarations: Vec<&'a Dependency>,
+    /// The installed (or locked) package, with the import names it provides.
+    pub package: Option<&'a Package>,
+    /// Where the project imports the package.
+    pub usages: Vec<&'a Import>,
+    /// Where the configuration of the project refers to the package.
+    pub config_usages: Vec<&'a ConfigUsage>,
+    /// The used packages that require it.
+    pub required_by: Vec<&'a PackageName>,
+}
+
+impl Explanation<'_> {
+    pub fn print_report(&self, config: &Config, mut stdout: impl Write) -> Result<ExitCode> {
+        match config.output {
+            OutputKind::Human => self.pretty_print(&mut stdout)?,
+            OutputKind::Json => {
+                let json = serde_json::to_string(&self).expect("Failed to serialize to JSON.");
+                writeln!(stdout, "{}", json)?;
+            }
+        }
+        stdout.flush()?;
+        Ok(ExitCode::Success)
+    }
+
+    /// A one-line reason for the state of the package.
+    fn verdict(&self) -> &'static str {
+        match self.state {
+            Some(PackageState::Used) if self.usages.is_empty() => {
+                "used: it is declared and referred to by the configuration of the project"
+            }
+            Some(PackageState::Used) => "used: it is declared and imported",
+            Some(PackageState::Unused) => {
+                "unused: it is declared, but neither imported nor required by a used package"
+            }
+            Some(PackageState::Untracked) => "untracked: it is imported, but not declared",
+            None if self.package.is_none() => "not reported: it is declared, but not installed",
+            None if !self.required_by.is_empty() => {
+                "not reported as unused: it is not imported, but a used package requires it"
+            }
+            None if self.declarations.is_empty() => {
+                "not reported: it is installed, but neither declared nor imported"
+            }
+            None => "not reported: none of its groups are selected",
+        }
+    }
+
+    fn pretty_print(&self, stdout: &mut impl Write) -> Result<()> {
+        writeln!(stdout, "\n 🔎 {} is {}", self.id, self.verdict())?;
+
+        writeln!(stdout, "\n 🗂️  Declared in")?;
+        if self.declarations.is_empty() {
+            writeln!(stdout, "    none")?;
+        }
+        for dep in &self.declarations {
+            let origins: Vec<String> = dep.origins().iter().map(format_origin).collect();
+            writeln!(
+                stdout,
+                "    {:<16} {:<10} {}",
+                dep.version(),
+                format!("({})", dep.group()),
+                origins.join(", ")
+            )?;
+        }
+
+        writeln!(stdout, "\n 📦 Provides")?;
+        let mut aliases: Vec<&String> = self
+            .package
+            .map(|pkg| pkg.aliases().iter().collect())
+            .unwrap_or_default();
+        aliases.sort();
+        if aliases.is_empty() {
+            writeln!(stdout, "    none, it is not installed")?;
+        }
+        for alias in aliases {
+            writeln!(stdout, "    {}", alias)?;
+        }
+
+        writeln!(stdout, "\n 🐍 Imported in")?;
+        if self.usages.is_empty() {
+            writeln!(stdout, "    none")?;
+        }
+        for import in &self.usages {
+            writeln!(
+                stdout,
+                "    {:<32} {}{}",
+                format_location(import.location()),
+                import.path(),
+                import_notes(Some(import.kind()), Some(import.context()))
+            )?;
+        }
+
+        writeln!(stdout, "\n ⚙️  Referenced in")?;
+        if self.config_usages.is_empty() {
+            writeln!(stdout, "    none")?;
+        }
+        for usage in &self.config_usages {
+            writeln!(
+                stdout,
+                "    {:<32} {}",
+                format!("{}:{}", usage.file().display(), usage.line()),
+                usage.reference()
+            )?;
+        }
+
+        writeln!(stdout, "\n 🔗 Required by used packages")?;
+        if self.required_by.is_empty() {
+            writeln!(stdout, "    none")?;
+        }
+        for id in &self.required_by {
+            writeln!(stdout, "    {}", id)?;
+        }
+
+        writeln!(stdout)?;
+        Ok(())
+    }
+}
+
 impl<'a> Outcome<'a> {
+    /// Group the package ids of the declared elements by their dependency group.
+    pub fn group_ids(&self) -> BTreeMap<String, Vec<String>> {
+        let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for element in &self.elements {
+            if let Some(dep) = element.dependency {
+                groups
+                    .entry(dep.group().to_string())
+                    .or_default()
+                    .push(element.package.id().to_string());
+            }
+        }
+        groups.values_mut().for_each(|ids| ids.sort());
+        groups
+    }
+
     pub fn print_report(&mut self, config: &Config, mut stdout: impl Write) -> Result<ExitCode> {
         match config.output {
             OutputKind::Human => self.pretty_print(&mut stdout, config),
@@ -40,7 +261,140 @@ impl<'a> Outcome<'a> {
         Ok(ExitCode::Success)
     }
 
+    fn write_errors(&self, stdout: &mut impl Write) -> Result<()> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+
+        // A notebook has an error for each cell that does not parse.
+        let files: HashSet<&Path> = self.errors.iter().map(FileError::file).collect();
+        writeln!(
+            stdout,
+            "\n ⚠️  {} file(s) could not be scanned, their imports may be missing",
+            files.len()
+        )?;
+        writeln!(stdout)?;
+        for error in &self.errors {
+            let location = error
+                .location()
+                .map_or_else(|| error.file().display().to_string(), format_location);
+            let kind = match error.kind() {
+                FileErrorKind::Read => "read error",
+                FileErrorKind::Encoding => "encoding error",
+                FileErrorKind::Notebook => "invalid notebook",
+                FileErrorKind::Parse => "syntax error",
+            };
+            writeln!(stdout, "    {}: {}: {}", location, kind, error.message())?;
+            if !error.recovered().is_empty() {
+                writeln!(
+                    stdout,
+                    "      {} import(s) recovered line by line, marked as `recovered`",
+                    error.recovered().len()
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_conflicts(&self, stdout: &mut impl Write) -> Result<()> {
+        if self.conflicts.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(stdout, "\n ⚠️  Conflicting versions")?;
+        for conflict in &self.conflicts {
+            writeln!(stdout, "\n    {} ({})", conflict.id(), conflict.group())?;
+            for dep in conflict.declarations() {
+                let origins: Vec<String> = dep.origins().iter().map(format_origin).collect();
+                writeln!(stdout, "      {:<16} {}", dep.version(), origins.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_backports(&self, stdout: &mut impl Write) -> Result<()> {
+        let Some(python_version) = self.python_version else {
+            return Ok(());
+        };
+        if self.backports.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(
+            stdout,
+            "\n ⚠️  Backports already provided by Python {}",
+            python_version
+        )?;
+        for backport in &self.backports {
+            writeln!(
+                stdout,
+                "\n    {} ({}): `{}` since Python {}",
+                backport.id(),
+                backport.group(),
+                backport.module(),
+                backport.since()
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_import_contexts(&self, stdout: &mut impl Write) -> Result<()> {
+        if !self.type_only.is_empty() {
+            writeln!(
+                stdout,
+                "\n 💡 Only imported for type checking, consider moving to a dev or typing group"
+            )?;
+            writeln!(stdout, "\n    {}", self.type_only.join(", "))?;
+        }
+        if !self.optional.is_empty() {
+            writeln!(
+                stdout,
+                "\n 🧩 Optional imports, guarded by `except ImportError`"
+            )?;
+            writeln!(stdout, "\n    {}", self.optional.join(", "))?;
+        }
+        Ok(())
+    }
+
+    fn write_misplaced(&self, stdout: &mut impl Write) -> Result<()> {
+        if !self.dev_in_production.is_empty() {
+            writeln!(stdout, "\n ⚠️  Dev dependencies used in production code")?;
+            writeln!(stdout)?;
+            for misplaced in &self.dev_in_production {
+                writeln!(
+                    stdout,
+                    "    {} ({}): {}",
+                    misplaced.id,
+                    misplaced.group,
+                    format_location(&misplaced.location)
+                )?;
+            }
+        }
+        if !self.test_only.is_empty() {
+            writeln!(
+                stdout,
+                "\n 💡 Production dependencies only used in tests, consider moving to a dev or test group"
+            )?;
+            writeln!(stdout)?;
+            for misplaced in &self.test_only {
+                writeln!(
+                    stdout,
+                    "    {}: {}",
+                    misplaced.id,
+                    format_location(&misplaced.location)
+                )?;
+            }
+        }
+        Ok(())
+    }
+
     fn pretty_print(&mut self, stdout: &mut impl Write, config: &Config) -> Result<ExitCode> {
+        self.write_errors(stdout)?;
+        self.write_conflicts(stdout)?;
+        self.write_backports(stdout)?;
+        self.write_import_contexts(stdout)?;
+        self.write_misplaced(stdout)?;
+
         if self.success {
             writeln!(
                 stdout,
@@ -55,30 +409,52 @@ impl<'a> Outcome<'a> {
 
         self.elements.sort_by_key(|e| Reverse(e.package.size()));
 
-        let records: Vec<Record> = self
-            .elements
-            .iter()
-            .map(|e| Record {
-                package: e.package.id(),
-                version: e.dependency.as_ref().map_or("N/A", |dep| dep.version()),
-                size: ByteSize::b(e.package.size()).to_string_as(true),
-            })
-            .collect();
+        // Untracked packages are not declared, so they do not belong to any group.
+        let mut groups: BTreeMap<Option<&str>, Vec<&AnalysisElement>> = BTreeMap::new();
+        for element in &self.elements {
+            groups
+                .entry(element.dependency.map(|dep| dep.group()))
+                .or_default()
+                .push(element);
+        }
+
+        for (group, elements) in groups {
+            if let Some(group) = group {
+                writeln!(stdout, "\n 🗂️  Group: {}", group)?;
+            }
+
+            let records: Vec<Record> = elements
+                .iter()
+                .map(|e| Record {
+                    package: format!("{}{}", e.package.id(), usage_notes(e)),
+                    version: e.dependency.as_ref().map_or("N/A", |dep| dep.version()),
+                    usages: e.usages.len() + e.config_usages.len(),
+                    size: ByteSize::b(e.package.size()).to_string_as(true),
+                })
+                .collect();
 
-        let mut table = Table::new(records);
-        table.with(Style::psql());
+            let mut table = Table::new(records);
+            table.with(Style::psql());
 
-        writeln!(stdout, "\n{}", table)?;
+            writeln!(stdout, "\n{}", table)?;
+        }
 
-        let total_size: u64 = self.elements.iter().map(|el| el.package.size()).sum();
+        // A package declared in several groups only takes up disk space once.
+        let mut counted = HashSet::new();
+        let total_size: u64 = self
+            .elements
+            .iter()
+            .filter(|el| counted.insert(el.package.id()))
+            .map(|el| el.package.size())
+            .sum();
         let total_size = ByteSize::b(total_size).to_string_as(true);
 
         let mut note = "".to_owned();
         note += " 💽 Total disk space: ";
         note += &total_size;
         note += "\n\n Note: There might be false-positives.\n";
-        note += "       For example, Unpack cannot detect usage of packages that are not imported under `[tool.poetry.*]`.\n";
-        note += "       Similarly, it can only detect declared packages in requirements.txt or pyproject.toml.\n";
+        note += "       For example, packages only used through plugins or entry points are not detected.\n";
+        note += "       Similarly, it only reads the dependency specification files at the root of the project.\n";
 
         writeln!(stdout, "\n{}", note)?;
 
@@ -0,0 +1,86 @@
+use std::collections::HashSet;
+
+use crate::project_assets::PackageName;
+
+/// Distributions whose import names cannot be derived from their name.
+/// Without an installed environment there is no `RECORD` or `top_level.txt` to read them from.
+const KNOWN_ALIASES: &[(&str, &[&str])] = &[
+    ("attrs", &["attr", "attrs"]),
+    ("azure-core", &["azure"]),
+    ("beautifulsoup4", &["bs4"]),
+    ("djangorestframework", &["rest_framework"]),
+    ("dnspython", &["dns"]),
+    ("docopt-ng", &["docopt"]),
+    ("factory-boy", &["factory"]),
+    ("faiss-cpu", &["faiss"]),
+    ("google-api-core", &["google"]),
+    ("google-auth", &["google"]),
+    ("google-cloud-storage", &["google"]),
+    ("googleapis-common-protos", &["google"]),
+    ("grpcio", &["grpc"]),
+    ("ipython", &["IPython"]),
+    ("msgpack-python", &["msgpack"]),
+    ("mysqlclient", &["MySQLdb"]),
+    ("opencv-contrib-python", &["cv2"]),
+    ("opencv-python", &["cv2"]),
+    ("opencv-python-headless", &["cv2"]),
+    ("pillow", &["PIL"]),
+    ("protobuf", &["google"]),
+    ("psycopg2-binary", &["psycopg2"]),
+    ("pycryptodome", &["Crypto"]),
+    ("pygithub", &["github"]),
+    ("pyjwt", &["jwt"]),
+    ("pymupdf", &["fitz", "pymupdf"]),
+    ("pyopenssl", &["OpenSSL"]),
+    ("pyserial", &["serial"]),
+    ("python-dateutil", &["dateutil"]),
+    ("python-dotenv", &["dotenv"]),
+    ("python-jose", &["jose"]),
+    ("python-magic", &["magic"]),
+    ("python-multipart", &["multipart", "python_multipart"]),
+    ("python-slugify", &["slugify"]),
+    (
+        "pywin32",
+        &["win32api", "win32con", "pywintypes", "win32com"],
+    ),
+    ("pyyaml", &["yaml"]),
+    ("pyzmq", &["zmq"]),
+    ("ruamel-yaml", &["ruamel"]),
+    ("scikit-image", &["skimage"]),
+    ("scikit-learn", &["sklearn"]),
+    (
+        "setuptools",
+        &["setuptools", "pkg_resources", "_distutils_hack"],
+    ),
+    ("zope-interface", &["zope"]),
+];
+
+/// The import names a distribution most likely provides.
+/// Falls back to the name itself with `-` replaced by `_`, e.g. `charset-normalizer` -> `charset_normalizer`.
+pub fn known_aliases(name: &PackageName) -> HashSet<String> {
+    match KNOWN_ALIASES.iter().find(|(known, _)| name == *known) {
+        Some((_, aliases)) => aliases.iter().map(ToString::to_string).collect(),
+        None => HashSet::from([name.as_str().replace('-', "_")]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_aliases() {
+        assert_eq!(
+            known_aliases(&PackageName::new("PyYAML")),
+            HashSet::from(["yaml".to_string()])
+        );
+        assert_eq!(
+            known_aliases(&PackageName::new("scikit_learn")),
+            HashSet::from(["sklearn".to_string()])
+        );
+        assert_eq!(
+            known_aliases(&PackageName::new("charset-normalizer")),
+            HashSet::from(["charset_normalizer".to_string()])
+        );
+    }
+}
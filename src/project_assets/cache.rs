@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::project_assets::Import;
+
+/// The directory, at the root of the project, the import cache is kept in.
+pub const CACHE_DIR: &str = ".unpack_cache";
+
+/// The file of the cache directory holding the imports of each file.
+const CACHE_FILE: &str = "imports.json";
+
+/// The size and modification time of a file, which tell cheaply whether it may have changed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    size: u64,
+    mtime_secs: u64,
+    mtime_nanos: u32,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        let mtime = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Ok(Self {
+            size: metadata.len(),
+            mtime_secs: mtime.as_secs(),
+            mtime_nanos: mtime.subsec_nanos(),
+        })
+    }
+}
+
+/// The offset basis and prime of the 64-bit FNV-1a hash.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Hash of the content of a file, used when its stamp changed but its content may not have,
+/// e.g. after a `git checkout`.
+/// It is FNV-1a rather than the standard library's hasher, whose output may change between Rust
+/// releases, so a cache written by one build stays valid for the next.
+pub fn content_hash(content: &str) -> u64 {
+    content.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// The imports found in one file, with what they were found from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub stamp: FileStamp,
+    pub hash: u64,
+    pub imports: Vec<Import>,
+}
+
+/// The imports of every file of the project from a previous run, keyed by path.
+/// A cache written by another version of unpack is discarded, as imports may be found differently.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ImportCache {
+    version: String,
+    entries: HashMap<PathBuf, CacheEntry>,
+}
+
+impl ImportCache {
+    pub fn new() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Reads the cache of the project, or starts an empty one if it is missing, unreadable or
+    /// from another version of unpack.
+    pub fn load(base_directory: &Path) -> Self {
+        let cache_file = base_directory.join(CACHE_DIR).join(CACHE_FILE);
+        fs::read_to_string(cache_file)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|cache| cache.version == env!("CARGO_PKG_VERSION"))
+            .unwrap_or_default()
+    }
+
+    /// Writes the cache to the project, with a `.gitignore` so it is never committed.
+    pub fn save(&self, base_directory: &Path) -> Result<()> {
+        let cache_dir = base_directory.join(CACHE_DIR);
+        fs::create_dir_all(&cache_dir)
+            .with_context(|| format!("Failed to create the cache directory {:?}", cache_dir))?;
+        fs::write(cache_dir.join(".gitignore"), "*\n")
+            .with_context(|| format!("Failed to write to {:?}", cache_dir))?;
+
+        let json = serde_json::to_string(self).context("Failed to serialize the cache")?;
+        fs::write(cache_dir.join(CACHE_FILE), json)
+            .with_context(|| format!("Failed to write to {:?}", cache_dir))?;
+        Ok(())
+    }
+
+    /// The cached entry of a file, if the file has not been touched since.
+    pub fn get_unchanged(&self, path: &Path, stamp: FileStamp) -> Option<&CacheEntry> {
+        self.entries.get(path).filter(|entry| entry.stamp == stamp)
+    }
+
+    /// The cached entry of a file, if its content is the same.
+    pub fn get_same_content(&self, path: &Path, hash: u64) -> Option<&CacheEntry> {
+        self.entries.get(path).filter(|entry| entry.hash == hash)
+    }
+
+    pub fn insert(&mut self, path: PathBuf, entry: CacheEntry) {
+        self.entries.insert(path, entry);
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.entries.remove(path);
+    }
+
+    /// A copy of the cache without the files that no longer exist.
+    /// The files a run leaves out, e.g. with `--include` or `--max-depth`, are kept for the next.
+    pub fn existing(&self) -> Self {
+        Self {
+            version: self.version.clone(),
+            entries: self
+                .entries
+                .iter()
+                .filter(|(path, _)| path.is_file())
+                .map(|(path, entry)| (path.clone(), entry.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl Default for ImportCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Removes the cache directory of the project, if there is one.
+pub fn clear_cache(base_directory: &Path) -> Result<()> {
+    let cache_dir = base_directory.join(CACHE_DIR);
+    if cache_dir.is_dir() {
+        fs::remove_dir_all(&cache_dir)
+            .with_context(|| format!("Failed to remove the cache directory {:?}", cache_dir))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    use crate::project_assets::{ImportKind, Location};
+
+    /// Helper function to create a cache entry with one import.
+    fn create_entry(file: &Path, hash: u64) -> CacheEntry {
+        let location = Location::new(file.to_path_buf(), None, 1, 8);
+        CacheEntry {
+            stamp: FileStamp::of(file).unwrap(),
+            hash,
+            imports: vec![Import::new(
+                "requests".to_string(),
+                ImportKind::Static,
+                location,
+            )],
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let file = temp_dir.path().join("app.py");
+        fs::write(&file, "import requests\n").unwrap();
+
+        let mut cache = ImportCache::new();
+        let entry = create_entry(&file, content_hash("import requests\n"));
+        cache.insert(file.clone(), entry.clone());
+        cache.save(temp_dir.path()).unwrap();
+
+        let cache = ImportCache::load(temp_dir.path());
+        let stamp = FileStamp::of(&file).unwrap();
+        assert_eq!(cache.get_unchanged(&file, stamp), Some(&entry));
+        assert!(temp_dir.path().join(CACHE_DIR).join(".gitignore").is_file());
+
+        clear_cache(temp_dir.path()).unwrap();
+        assert!(!temp_dir.path().join(CACHE_DIR).exists());
+        assert!(ImportCache::load(temp_dir.path()).entries.is_empty());
+    }
+
+    #[test]
+    fn test_stale_entries() {
+        let temp_dir = tempdir().unwrap();
+        let file = temp_dir.path().join("app.py");
+        fs::write(&file, "import requests\n").unwrap();
+
+        let mut cache = ImportCache::new();
+        let hash = content_hash("import requests\n");
+        cache.insert(file.clone(), create_entry(&file, hash));
+
+        // The stamp changes with the content, and the entry is only reused for the old content.
+        fs::write(&file, "import requests\nimport yaml\n").unwrap();
+        let stamp = FileStamp::of(&file).unwrap();
+        assert!(cache.get_unchanged(&file, stamp).is_none());
+        assert!(cache.get_same_content(&file, hash).is_some());
+        assert!(cache
+            .get_same_content(&file, content_hash("import requests\nimport yaml\n"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_content_hash() {
+        // Known FNV-1a values, which must not change between builds.
+        assert_eq!(content_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(content_hash("import os\n"), content_hash("import io\n"));
+    }
+
+    #[test]
+    fn test_existing_entries() {
+        let temp_dir = tempdir().unwrap();
+        let kept = temp_dir.path().join("app.py");
+        fs::write(&kept, "import requests\n").unwrap();
+        let removed = temp_dir.path().join("cli.py");
+        fs::write(&removed, "import requests\n").unwrap();
+
+        let mut cache = ImportCache::new();
+        cache.insert(kept.clone(), create_entry(&kept, 0));
+        cache.insert(removed.clone(), create_entry(&removed, 0));
+        fs::remove_file(&removed).unwrap();
+
+        let cache = cache.existing();
+        assert!(cache.get_same_content(&kept, 0).is_some());
+        assert!(cache.get_same_content(&removed, 0).is_none());
+    }
+
+    #[test]
+    fn test_other_version() {
+        let temp_dir = tempdir().unwrap();
+        let file = temp_dir.path().join("app.py");
+        fs::write(&file, "import requests\n").unwrap();
+
+        let mut cache = ImportCache::new();
+        cache.insert(file.clone(), create_entry(&file, 0));
+        cache.version = "0.0.0".to_string();
+        cache.save(temp_dir.path()).unwrap();
+
+        assert!(ImportCache::load(temp_dir.path()).entries.is_empty());
+
+        fs::write(temp_dir.path().join(CACHE_DIR).join(CACHE_FILE), "not json").unwrap();
+        assert!(ImportCache::load(temp_dir.path()).entries.is_empty());
+    }
+}
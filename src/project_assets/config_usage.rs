@@ -0,0 +1,765 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::project_assets::walk::project_walker;
+use crate::project_assets::{Package, PackageName};
+
+/// Configuration files at the root of the project that configure tools.
+const ROOT_CONFIG_FILES: [(&str, FileKind); 3] = [
+    ("pyproject.toml", FileKind::Pyproject),
+    ("setup.cfg", FileKind::Ini),
+    ("tox.ini", FileKind::Ini),
+];
+
+/// Keys whose values list packages to install rather than tools to run, e.g. `deps` in `tox.ini`.
+const INSTALL_KEYS: [&str; 8] = [
+    "dependencies",
+    "dev-dependencies",
+    "optional-dependencies",
+    "requires",
+    "deps",
+    "install_requires",
+    "extras_require",
+    "tests_require",
+];
+
+/// The transports of Celery brokers and result backends, with the module each one imports.
+///  e.g. `redis://localhost:6379/0` -> `redis`, `db+postgresql://...` -> `sqlalchemy`
+const CELERY_TRANSPORTS: [(&str, &str); 16] = [
+    ("redis", "redis"),
+    ("rediss", "redis"),
+    ("sentinel", "redis"),
+    ("amqp", "amqp"),
+    ("amqps", "amqp"),
+    ("pyamqp", "amqp"),
+    ("librabbitmq", "librabbitmq"),
+    ("sqs", "boto3"),
+    ("s3", "boto3"),
+    ("dynamodb", "boto3"),
+    ("mongodb", "pymongo"),
+    ("db", "sqlalchemy"),
+    ("django-db", "django_celery_results"),
+    ("memcached", "pylibmc"),
+    ("memcache", "memcache"),
+    ("elasticsearch", "elasticsearch"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileKind {
+    /// The `[tool.*]` tables of a `pyproject.toml`
+    Pyproject,
+    /// `setup.cfg` or `tox.ini`
+    Ini,
+    /// A `Makefile`, `Dockerfile`, `Procfile` or shell script
+    Script,
+    /// Django settings or a Celery configuration, e.g. `settings.py` or `celeryconfig.py`
+    Settings,
+}
+
+/// What a line of configuration refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reference {
+    /// A command, e.g. `gunicorn` in a `Procfile`, or a tool section, e.g. `[tool.mypy]`
+    Command(String),
+    /// A module path, e.g. `uvicorn.workers.UvicornWorker` or `pytest` in `python -m pytest`
+    Module(String),
+}
+
+impl Reference {
+    fn as_str(&self) -> &str {
+        match self {
+            Reference::Command(command) => command,
+            Reference::Module(path) => path,
+        }
+    }
+
+    /// Whether the reference is to one of the commands or import names of the package.
+    fn refers_to(&self, package: &Package) -> bool {
+        match self {
+            Reference::Command(command) => package.commands().contains(command),
+            Reference::Module(path) => package.aliases().iter().any(|alias| {
+                path.strip_prefix(alias.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', ':']))
+            }),
+        }
+    }
+}
+
+/// A reference to a package outside of the Python code of the project.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigUsage {
+    file: PathBuf,
+    line: usize,
+    /// The command or module path that refers to the package.
+    reference: String,
+}
+
+impl ConfigUsage {
+    pub fn new(file: PathBuf, line: usize, reference: String) -> Self {
+        Self {
+            file,
+            line,
+            reference,
+        }
+    }
+
+    pub fn file(&self) -> &PathBuf {
+        &self.file
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+}
+
+/// Words that come before the command of a line, e.g. `RUN` in a `Dockerfile`
+const COMMAND_PREFIXES: [&str; 9] = [
+    "RUN",
+    "CMD",
+    "ENTRYPOINT",
+    "exec",
+    "sudo",
+    "env",
+    "time",
+    "nohup",
+    "command",
+];
+
+/// Commands whose arguments are packages to install rather than tools to run,
+///  e.g. `pip install black` or `poetry add isort`
+const INSTALL_COMMANDS: [&[&str]; 8] = [
+    &["pip"],
+    &["pip3"],
+    &["-m", "pip"],
+    &["uv", "pip"],
+    &["uv", "add"],
+    &["poetry", "add"],
+    &["pdm", "add"],
+    &["pipx", "install"],
+];
+
+/// Tables of a `pyproject.toml` that hold package metadata rather than tool configuration,
+///  e.g. the `description` of `[tool.poetry]`
+const METADATA_TABLES: [&str; 3] = ["tool.poetry", "tool.poetry.extras", "tool.poetry.urls"];
+
+/// A word of a command line without the quotes and punctuation around it,
+///  e.g. `["gunicorn",` -> `gunicorn`, `@black` -> `black`
+fn clean_word(word: &str) -> &str {
+    word.trim_matches(|c: char| !(c.is_alphanumeric() || "_.:-".contains(c)))
+        .trim_end_matches(['.', ':'])
+}
+
+/// Whether a word comes before the command of a line: a label, e.g. `web:` in a `Procfile`, a
+/// variable, e.g. `DEBUG=1`, or a prefix, e.g. `RUN` or `exec`.
+fn is_command_prefix(word: &str) -> bool {
+    word.ends_with(':')
+        || (word.contains('=') && !word.starts_with('-'))
+        || COMMAND_PREFIXES.contains(&clean_word(word))
+}
+
+/// The reference of a word, which is a module path if it is dotted.
+fn word_reference(word: &str) -> Option<Reference> {
+    if !word.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        return None;
+    }
+    Some(if word.contains(['.', ':']) {
+        Reference::Module(word.to_string())
+    } else {
+        Reference::Command(word.to_string())
+    })
+}
+
+/// The references of a single command: its first word, the one after `run` or `exec`, e.g.
+/// `poetry run pytest`, the module after `-m`, e.g. `python -m pytest`, and the module paths
+/// among its arguments, e.g. `uvicorn.workers.UvicornWorker`.
+/// Install commands, e.g. `pip install black`, refer to nothing.
+fn command_references(command: &str) -> Vec<Reference> {
+    let words: Vec<&str> = command
+        .split_whitespace()
+        .skip_while(|word| is_command_prefix(word))
+        .map(clean_word)
+        .filter(|word| !word.is_empty())
+        .collect();
+    let is_install = INSTALL_COMMANDS.iter().any(|install| {
+        words
+            .windows(install.len())
+            .any(|window| window == *install)
+    });
+    if is_install {
+        return Vec::new();
+    }
+
+    let mut references = Vec::new();
+    let mut expect_command = true;
+    let mut expect_module = false;
+    let mut after_command = false;
+    for word in words {
+        if expect_module {
+            references.push(Reference::Module(word.to_string()));
+            expect_module = false;
+        } else if word == "-m" {
+            expect_module = true;
+            expect_command = false;
+        } else if word.starts_with('-') {
+            continue;
+        } else if expect_command {
+            references.extend(word_reference(word));
+            expect_command = false;
+            after_command = true;
+            continue;
+        } else if after_command && (word == "run" || word == "exec") {
+            expect_command = true;
+        } else if word.contains(['.', ':']) {
+            references.extend(word_reference(word));
+        }
+        after_command = false;
+    }
+    references
+}
+
+/// The commands and module paths of a command line, which may chain several commands with
+/// `&&`, `;` or `|`.
+///  e.g. `web: gunicorn -k uvicorn.workers.UvicornWorker app:app` -> `gunicorn`,
+///  `uvicorn.workers.UvicornWorker` and `app:app`
+fn line_references(text: &str) -> Vec<Reference> {
+    text.split([';', '|', '&'])
+        .flat_map(command_references)
+        .collect()
+}
+
+/// The string literals of a line of TOML or Python,
+///  e.g. `plugins = ["pydantic.mypy"]` -> `pydantic.mypy`
+fn quoted_strings(value: &str) -> impl Iterator<Item = &str> {
+    value.split(['"', '\'']).skip(1).step_by(2)
+}
+
+/// The name of a section header, e.g. `[tool.black]` -> `tool.black`
+fn section_name(line: &str) -> Option<&str> {
+    line.trim_start_matches('[')
+        .strip_suffix(']')
+        .filter(|_| line.starts_with('['))
+        .map(|name| name.trim_end_matches(']').trim())
+}
+
+/// Splits a `key = value` (or `key: value`) line.
+fn split_key(line: &str) -> Option<(&str, &str)> {
+    let idx = line.find(['=', ':'])?;
+    Some((line[..idx].trim().trim_matches('"'), &line[idx + 1..]))
+}
+
+/// The references in the `[tool.*]` tables of a `pyproject.toml`. The name of each table is a
+/// reference to the tool it configures, e.g. `[tool.mypy]`, and each string value is a command
+/// line. The package metadata of Poetry is skipped.
+fn pyproject_references(content: &str) -> Vec<(usize, Reference)> {
+    let mut references = Vec::new();
+    let mut in_tool = false;
+    let mut in_metadata = false;
+    let mut in_install_array = false;
+
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if in_install_array {
+            in_install_array = !line.ends_with(']');
+            continue;
+        }
+        if let Some(name) = section_name(line) {
+            let mut parts = name.split('.').map(|part| part.trim_matches('"'));
+            in_tool = parts.next() == Some("tool") && !name.contains("dependencies");
+            in_metadata = METADATA_TABLES.contains(&name.replace('"', "").as_str());
+            if let Some(tool) = parts.next().filter(|_| in_tool) {
+                references.push((idx + 1, Reference::Command(tool.to_string())));
+            }
+            continue;
+        }
+        if !in_tool || in_metadata || line.starts_with('#') {
+            continue;
+        }
+        // TOML keys are followed by `=`, and array items may hold colons, e.g. `"app:main",`
+        let key_value = line
+            .split_once('=')
+            .map(|(key, value)| (key.trim().trim_matches('"'), value));
+        let value = match key_value {
+            Some((key, value)) if INSTALL_KEYS.contains(&key) => {
+                let value = value.trim();
+                in_install_array = value.starts_with('[') && !value.ends_with(']');
+                continue;
+            }
+            Some((_, value)) => value,
+            None => line,
+        };
+        references.extend(
+            quoted_strings(value)
+                .flat_map(line_references)
+                .map(|r| (idx + 1, r)),
+        );
+    }
+    references
+}
+
+/// The references in a `setup.cfg` or `tox.ini`. A `[tool:<name>]` section is a reference to
+/// the tool it configures, e.g. `[tool:pytest]`, while other section names, e.g. `[testenv]`,
+/// name no package. The package metadata of `setup.cfg` is skipped.
+fn ini_references(content: &str) -> Vec<(usize, Reference)> {
+    let mut references = Vec::new();
+    let mut in_metadata = false;
+    let mut in_install_value = false;
+
+    for (idx, line) in content.lines().enumerate() {
+        // Indented lines continue the value of the previous key.
+        let is_continuation = line.starts_with([' ', '\t']);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+        if is_continuation && in_install_value {
+            continue;
+        }
+        in_install_value = false;
+
+        if let Some(name) = section_name(line) {
+            in_metadata = name == "metadata" || name == "options" || name.starts_with("options.");
+            let tool = name
+                .strip_prefix("tool:")
+                .and_then(|tool| tool.split([':', '.', '-', ' ']).next())
+                .filter(|tool| !tool.is_empty());
+            if let Some(tool) = tool.filter(|_| !in_metadata) {
+                references.push((idx + 1, Reference::Command(tool.to_string())));
+            }
+            continue;
+        }
+        if in_metadata {
+            continue;
+        }
+        let value = match split_key(line).filter(|_| !is_continuation) {
+            Some((key, _)) if INSTALL_KEYS.contains(&key) => {
+                in_install_value = true;
+                continue;
+            }
+            Some((_, value)) => value,
+            None => line,
+        };
+        references.extend(line_references(value).into_iter().map(|r| (idx + 1, r)));
+    }
+    references
+}
+
+/// The references in a `Makefile`, `Dockerfile`, `Procfile` or shell script.
+fn script_references(content: &str) -> Vec<(usize, Reference)> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim_start().starts_with('#'))
+        .flat_map(|(idx, line)| {
+            line_references(line)
+                .into_iter()
+                .map(move |reference| (idx + 1, reference))
+        })
+        .collect()
+}
+
+/// The module a Celery broker or result backend URL imports,
+///  e.g. `redis://localhost:6379/0` -> `redis`, `cache+memcached://` -> `pylibmc`
+fn celery_transport(url: &str) -> Option<&'static str> {
+    let scheme = url.split("://").next().unwrap_or_default();
+    scheme.split('+').find_map(|part| {
+        CELERY_TRANSPORTS
+            .iter()
+            .find(|(transport, _)| *transport == part)
+            .map(|(_, module)| *module)
+    })
+}
+
+/// The references in Django settings or a Celery configuration: the apps of `INSTALLED_APPS`,
+/// or of the lists it is made of, e.g. `THIRD_PARTY_APPS`, and the modules the broker and result
+/// backend URLs import, e.g. `CELERY_BROKER_URL = "redis://..."` or `result_backend = "db+..."`
+fn settings_references(content: &str) -> Vec<(usize, Reference)> {
+    let mut references = Vec::new();
+    let mut in_apps = false;
+
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        let apps = if in_apps {
+            Some(line)
+        } else {
+            // e.g. `INSTALLED_APPS = [` or `INSTALLED_APPS += ("debug_toolbar",)`
+            line.split_once('=')
+                .filter(|(key, _)| key.trim_end_matches('+').trim().ends_with("_APPS"))
+                .map(|(_, value)| value)
+        };
+        if let Some(apps) = apps {
+            references.extend(
+                quoted_strings(apps).map(|app| (idx + 1, Reference::Module(app.to_string()))),
+            );
+            in_apps = !apps.contains([']', ')']);
+            continue;
+        }
+
+        let lowercase = line.to_lowercase();
+        if lowercase.contains("broker") || lowercase.contains("backend") {
+            references.extend(
+                quoted_strings(line)
+                    .filter_map(celery_transport)
+                    .map(|module| (idx + 1, Reference::Module(module.to_string()))),
+            );
+        }
+    }
+    references
+}
+
+/// Whether a Python file holds Django settings or a Celery configuration,
+///  e.g. `settings.py`, `settings_prod.py`, `settings/base.py` or `celeryconfig.py`
+fn is_settings(path: &Path) -> bool {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    if !file_name.ends_with(".py") {
+        return false;
+    }
+    let in_settings_package = path
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|dir| dir == "settings");
+    file_name.starts_with("settings")
+        || in_settings_package
+        || file_name == "celeryconfig.py"
+        || file_name == "celery.py"
+}
+
+/// Whether a file runs commands, e.g. `Makefile`, `Dockerfile.dev`, `Procfile` or `deploy.sh`
+fn is_script(file_name: &str) -> bool {
+    matches!(
+        file_name,
+        "Makefile" | "makefile" | "GNUmakefile" | "Procfile"
+    ) || file_name.starts_with("Dockerfile")
+        || file_name.ends_with(".dockerfile")
+        || file_name.ends_with(".sh")
+        || file_name.ends_with(".bash")
+}
+
+/// The configuration files of the project: `pyproject.toml`, `setup.cfg` and `tox.ini` at its
+/// root, and the scripts and settings anywhere in it but in excluded paths.
+fn config_files(config: &Config) -> Result<Vec<(PathBuf, FileKind)>> {
+    let mut files: Vec<(PathBuf, FileKind)> = ROOT_CONFIG_FILES
+        .iter()
+        .map(|(file_name, kind)| (config.base_directory.join(file_name), *kind))
+        .filter(|(path, _)| path.is_file())
+        .collect();
+
+    // The include globs select the Python files to scan, so they do not apply to scripts.
+    let walker = project_walker(config, &[])?.build();
+    files.extend(
+        walker
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+            .filter_map(|entry| {
+                if entry.file_name().to_str().is_some_and(is_script) {
+                    Some((entry.into_path(), FileKind::Script))
+                } else if is_settings(entry.path()) {
+                    Some((entry.into_path(), FileKind::Settings))
+                } else {
+                    None
+                }
+            }),
+    );
+    Ok(files)
+}
+
+/// Finds the packages used without being imported: the ones whose commands or module paths
+/// appear in the configuration files of the project, e.g. `gunicorn` in a `Procfile`, `mypy`
+/// in `[tool.mypy]`, `python -m pytest` in a `Makefile` or a Django app in `INSTALLED_APPS`.
+pub fn get_config_usages(
+    config: &Config,
+    packages: &HashSet<Package>,
+) -> Result<HashMap<PackageName, Vec<ConfigUsage>>> {
+    let mut usages: HashMap<PackageName, Vec<ConfigUsage>> = HashMap::new();
+
+    for (file, kind) in config_files(config)? {
+        let Ok(content) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let references = match kind {
+            FileKind::Pyproject => pyproject_references(&content),
+            FileKind::Ini => ini_references(&content),
+            FileKind::Script => script_references(&content),
+            FileKind::Settings => settings_references(&content),
+        };
+
+        for (line, reference) in references {
+            for package in packages.iter().filter(|pkg| reference.refers_to(pkg)) {
+                usages
+                    .entry(package.name().clone())
+                    .or_default()
+                    .push(ConfigUsage::new(
+                        file.clone(),
+                        line,
+                        reference.as_str().to_string(),
+                    ));
+            }
+        }
+    }
+    Ok(usages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    use crate::project_assets::test_utils::te_config;
+    use crate::project_assets::PackageBuilder;
+
+    /// Helper function to create a Package instance with commands.
+    fn create_package(id: &str, aliases: &[&str], commands: &[&str]) -> Package {
+        let aliases = aliases.iter().map(|s| s.to_string()).collect();
+        let commands = commands.iter().map(|s| s.to_string()).collect();
+        PackageBuilder::new(id.to_string(), aliases, 0, HashSet::new())
+            .commands(commands)
+            .build()
+    }
+
+    /// Helper function to get the lines and texts of references.
+    fn texts(references: Vec<(usize, Reference)>) -> Vec<(usize, String)> {
+        references
+            .into_iter()
+            .map(|(line, reference)| (line, reference.as_str().to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_line_references() {
+        assert_eq!(
+            line_references("web: gunicorn -k uvicorn.workers.UvicornWorker app:app"),
+            vec![
+                Reference::Command("gunicorn".to_string()),
+                Reference::Module("uvicorn.workers.UvicornWorker".to_string()),
+                Reference::Module("app:app".to_string()),
+            ]
+        );
+        assert_eq!(
+            line_references("\tpython -m pytest tests/ && poetry run mypy src"),
+            vec![
+                Reference::Command("python".to_string()),
+                Reference::Module("pytest".to_string()),
+                Reference::Command("poetry".to_string()),
+                Reference::Command("mypy".to_string()),
+            ]
+        );
+        assert_eq!(
+            line_references(r#"CMD ["uvicorn", "app.main:app", "--port", "80"]"#),
+            vec![
+                Reference::Command("uvicorn".to_string()),
+                Reference::Module("app.main:app".to_string()),
+            ]
+        );
+    }
+
+    /// The packages installed by a line are not used by it.
+    #[test]
+    fn test_install_lines() {
+        assert!(line_references("RUN pip install gunicorn flask-cors").is_empty());
+        assert!(line_references("pip install black isort").is_empty());
+        assert!(line_references("python -m pip install -U black").is_empty());
+        assert!(line_references("uv pip install ruff").is_empty());
+        assert!(line_references("poetry add --group dev pytest").is_empty());
+        assert_eq!(
+            line_references("pip install -r requirements.txt && exec gunicorn app:app"),
+            vec![
+                Reference::Command("gunicorn".to_string()),
+                Reference::Module("app:app".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pyproject_references() {
+        let content = r#"
+[project]
+name = "my-project"
+dependencies = ["black"]
+
+[tool.mypy]
+plugins = ["pydantic.mypy"]
+
+[tool.poetry.group.dev.dependencies]
+ruff = "^0.1"
+
+[tool.hatch.envs.lint]
+dependencies = [
+    "isort",
+]
+scripts = { check = "flake8 src" }
+
+[tool.poetry]
+description = "black and isort for notebooks"
+
+[tool.poetry.extras]
+yaml = ["pyyaml"]
+
+[tool.poetry.scripts]
+my-cli = "my_project.cli:main"
+"#;
+
+        assert_eq!(
+            texts(pyproject_references(content)),
+            vec![
+                (6, "mypy".to_string()),
+                (7, "pydantic.mypy".to_string()),
+                (12, "hatch".to_string()),
+                (16, "flake8".to_string()),
+                (18, "poetry".to_string()),
+                (21, "poetry".to_string()),
+                (24, "poetry".to_string()),
+                (25, "my_project.cli:main".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ini_references() {
+        let content = r#"
+[metadata]
+name = my-project
+
+[options]
+install_requires =
+    black
+
+[tool:pytest]
+addopts = --cov
+
+[testenv]
+deps =
+    isort
+commands =
+    pip install -e .
+    coverage run -m pytest
+"#;
+
+        assert_eq!(
+            texts(ini_references(content)),
+            vec![
+                (9, "pytest".to_string()),
+                (17, "coverage".to_string()),
+                (17, "pytest".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_settings_references() {
+        let content = r#"
+DJANGO_APPS = ["django.contrib.admin", "django.contrib.auth"]
+THIRD_PARTY_APPS = [
+    "rest_framework",
+    # "debug_toolbar",
+    "corsheaders.apps.CorsHeadersConfig",
+]
+INSTALLED_APPS = DJANGO_APPS + THIRD_PARTY_APPS
+INSTALLED_APPS += ("django_extensions",)
+
+CELERY_BROKER_URL = "redis://localhost:6379/0"
+CELERY_RESULT_BACKEND = "db+postgresql://localhost/results"
+CACHE_URL = "memcached://localhost:11211"
+"#;
+
+        assert_eq!(
+            texts(settings_references(content)),
+            vec![
+                (2, "django.contrib.admin".to_string()),
+                (2, "django.contrib.auth".to_string()),
+                (4, "rest_framework".to_string()),
+                (6, "corsheaders.apps.CorsHeadersConfig".to_string()),
+                (9, "django_extensions".to_string()),
+                (11, "redis".to_string()),
+                (12, "sqlalchemy".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_settings() {
+        assert!(is_settings(Path::new("mysite/settings.py")));
+        assert!(is_settings(Path::new("mysite/settings/production.py")));
+        assert!(is_settings(Path::new("celeryconfig.py")));
+        assert!(!is_settings(Path::new("settings.toml")));
+        assert!(!is_settings(Path::new("app/views.py")));
+    }
+
+    #[test]
+    fn test_config_usages() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("Procfile"),
+            "web: gunicorn -k uvicorn.workers.UvicornWorker app:app\n",
+        )
+        .unwrap();
+        fs::create_dir(temp_dir.path().join("scripts")).unwrap();
+        fs::write(
+            temp_dir.path().join("scripts/lint.sh"),
+            "#!/bin/sh\n# black is run by pre-commit\nmypy src\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\ndependencies = [\"black\"]\n\n[tool.poetry.extras]\nyaml = [\"pyyaml\"]\n",
+        )
+        .unwrap();
+        fs::create_dir(temp_dir.path().join("mysite")).unwrap();
+        fs::write(
+            temp_dir.path().join("mysite/settings.py"),
+            "INSTALLED_APPS = [\"rest_framework\"]\nCELERY_BROKER_URL = \"redis://cache:6379\"\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("Dockerfile"),
+            "FROM python:3.12\nRUN pip install flask-cors\n",
+        )
+        .unwrap();
+
+        let packages = HashSet::from([
+            create_package("gunicorn", &["gunicorn"], &["gunicorn"]),
+            create_package("uvicorn", &["uvicorn"], &["uvicorn"]),
+            create_package("mypy", &["mypy", "mypyc"], &["mypy", "dmypy"]),
+            create_package("black", &["black"], &["black"]),
+            // Packages read from a lock file have their name as command.
+            create_package("pyyaml", &["yaml"], &["pyyaml"]),
+            create_package("flask-cors", &["flask_cors"], &["flask-cors"]),
+            create_package("djangorestframework", &["rest_framework"], &[]),
+            create_package("redis", &["redis"], &[]),
+        ]);
+
+        let usages =
+            get_config_usages(&te_config(temp_dir.path().to_path_buf()), &packages).unwrap();
+
+        let mut used: Vec<&str> = usages.keys().map(PackageName::as_str).collect();
+        used.sort();
+        assert_eq!(
+            used,
+            vec![
+                "djangorestframework",
+                "gunicorn",
+                "mypy",
+                "redis",
+                "uvicorn"
+            ]
+        );
+        let mypy = &usages[&PackageName::new("mypy")];
+        assert_eq!(mypy.len(), 1);
+        assert_eq!(mypy[0].file(), &temp_dir.path().join("scripts/lint.sh"));
+        assert_eq!(mypy[0].line(), 3);
+        assert_eq!(mypy[0].reference(), "mypy");
+    }
+}
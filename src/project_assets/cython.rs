@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::project_assets::line_scan::scan_imports;
+use crate::project_assets::{Import, ImportKind, Location};
+
+/// Declarations bundled with Cython itself, which no installed package provides.
+///  e.g. `from libc.stdlib cimport malloc` or `from cpython.ref cimport PyObject`
+const CYTHON_INCLUDES: [&str; 4] = ["libc", "libcpp", "cpython", "posix"];
+
+/// Extracts the imports of a Cython source (`.pyx`, `.pxd` or `.pxi`), which the Python parser
+/// does not understand: `import x`, `cimport x`, `from x import y` and `from x cimport y`.
+pub fn cython_imports(path: &Path, source: &str) -> HashSet<Import> {
+    scan_imports(source)
+        .into_iter()
+        .filter(|import| !CYTHON_INCLUDES.contains(&stem(&import.path)))
+        .map(|import| {
+            let location = Location::new(path.to_path_buf(), None, import.line + 1, import.column);
+            Import::new(import.path, ImportKind::Static, location)
+        })
+        .collect()
+}
+
+/// The top-level module of a dotted path, e.g. `libc.stdlib` -> `libc`
+fn stem(path: &str) -> &str {
+    path.split('.').next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cython_imports() {
+        let source = r#"# cython: language_level=3
+cimport numpy as cnp
+import numpy as np, scipy  # runtime
+from libc.stdlib cimport malloc, free
+from cpython.ref cimport PyObject
+from sklearn.utils cimport (
+    _random,
+    _openmp_helpers,
+)
+from . cimport helpers
+
+cdef extern from "math.h":
+    double sqrt(double x)
+
+def f():
+    from pandas import \
+        DataFrame
+"#;
+
+        let imports = cython_imports(Path::new("fast.pyx"), source);
+        let mut paths: Vec<&str> = imports.iter().map(Import::path).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "numpy",
+                "numpy",
+                "pandas.DataFrame",
+                "scipy",
+                "sklearn.utils._openmp_helpers",
+                "sklearn.utils._random",
+            ]
+        );
+
+        // Continued statements are located at their first line.
+        let pandas = imports.iter().find(|import| import.name() == "pandas");
+        assert_eq!(
+            pandas.map(Import::location),
+            Some(&Location::new(
+                Path::new("fast.pyx").to_path_buf(),
+                None,
+                16,
+                5
+            ))
+        );
+    }
+}
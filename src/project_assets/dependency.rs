@@ -1,42 +1,169 @@
 extern crate bytesize;
 extern crate fs_extra;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::hash::Hash;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::cli::DepType;
 use crate::config::Config;
+use crate::project_assets::pep503::PackageName;
+use crate::project_assets::pep508::{parse_requirement, Requirement};
+use crate::project_assets::pep621::get_pep621_dependencies;
+use crate::project_assets::pep735::get_pep735_dependencies;
+use crate::project_assets::pipfile::get_pipfile_dependencies;
+use crate::project_assets::setuptools::{get_setup_cfg_dependencies, get_setup_py_dependencies};
+
+/// Where a dependency was declared.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone, Eq, Hash)]
+pub struct Origin {
+    file: PathBuf,
+    line: Option<usize>,
+}
+
+impl Origin {
+    pub fn new(file: PathBuf, line: Option<usize>) -> Self {
+        Self { file, line }
+    }
+
+    #[allow(dead_code)]
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    #[allow(dead_code)]
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+}
+
+/// Where a dependency is installed from.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone, Eq, Hash, Default)]
+#[serde(tag = "kind", content = "location", rename_all = "lowercase")]
+pub enum Source {
+    /// A package index such as PyPI
+    #[default]
+    Registry,
+    /// A git repository, e.g. `git+https://github.com/org/repo.git@v1.0`
+    Git(String),
+    /// A local directory or archive, e.g. `../bar`
+    Path(String),
+    /// A remote archive, e.g. `https://host/pkgs/foo-1.0.tar.gz`
+    Url(String),
+}
+
+impl Source {
+    /// Classify a direct reference as found in a PEP 508 `name @ <reference>` or a requirements file.
+    pub fn from_reference(reference: &str) -> Self {
+        if reference.starts_with("git+") {
+            Source::Git(reference.to_string())
+        } else if reference.starts_with("file:")
+            || reference.starts_with('.')
+            || reference.starts_with('/')
+        {
+            Source::Path(reference.to_string())
+        } else {
+            Source::Url(reference.to_string())
+        }
+    }
+}
+
+/// The group of dependencies a project needs at runtime.
+pub const MAIN_GROUP: &str = "main";
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Clone, Eq, Hash)]
 pub struct Dependency {
-    id: String,
+    id: PackageName,
     version: Option<String>,
+    extras: Vec<String>,
+    marker: Option<String>,
+    group: String,
+    /// Whether the group is an extra of the project, e.g. `[project.optional-dependencies]`, which
+    /// users install to run it rather than to develop it.
+    #[serde(default)]
+    optional: bool,
+    source: Source,
+    /// Every place the dependency is declared, in the order the files were read.
+    origins: Vec<Origin>,
 }
 
 impl Dependency {
+    #[allow(dead_code)]
     pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn name(&self) -> &PackageName {
         &self.id
     }
 
     pub fn version(&self) -> &str {
         self.version.as_deref().unwrap_or("N/A")
     }
+
+    #[allow(dead_code)]
+    pub fn extras(&self) -> &[String] {
+        &self.extras
+    }
+
+    #[allow(dead_code)]
+    pub fn marker(&self) -> Option<&str> {
+        self.marker.as_deref()
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+
+    #[allow(dead_code)]
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// Where the dependency was first declared.
+    #[allow(dead_code)]
+    pub fn origin(&self) -> Option<&Origin> {
+        self.origins.first()
+    }
+
+    #[allow(dead_code)]
+    pub fn origins(&self) -> &[Origin] {
+        &self.origins
+    }
 }
 
 pub struct DependencyBuilder {
-    id: String,
+    id: PackageName,
     version: Option<String>,
+    extras: Vec<String>,
+    marker: Option<String>,
+    group: String,
+    optional: bool,
+    source: Source,
+    origins: Vec<Origin>,
 }
 
 impl DependencyBuilder {
     pub fn new(id: String) -> Self {
-        Self { id, version: None }
+        Self {
+            id: PackageName::new(&id),
+            version: None,
+            extras: Vec::new(),
+            marker: None,
+            group: MAIN_GROUP.to_string(),
+            optional: false,
+            source: Source::Registry,
+            origins: Vec::new(),
+        }
     }
 
     pub fn version(mut self, version: String) -> Self {
@@ -44,11 +171,79 @@ impl DependencyBuilder {
         self
     }
 
+    pub fn extras(mut self, extras: Vec<String>) -> Self {
+        self.extras = extras;
+        self
+    }
+
+    pub fn marker(mut self, marker: String) -> Self {
+        self.marker = Some(marker);
+        self
+    }
+
+    pub fn group(mut self, group: String) -> Self {
+        self.group = group;
+        self
+    }
+
+    /// Marks the group as an extra of the project.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    pub fn source(mut self, source: Source) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn origin(mut self, origin: Origin) -> Self {
+        self.origins.push(origin);
+        self
+    }
+
     pub fn build(self) -> Dependency {
         Dependency {
             id: self.id,
             version: self.version,
+            extras: self.extras,
+            marker: self.marker,
+            group: self.group,
+            optional: self.optional,
+            source: self.source,
+            origins: self.origins,
+        }
+    }
+}
+
+impl From<Requirement> for DependencyBuilder {
+    fn from(requirement: Requirement) -> Self {
+        let version = requirement.specifier_set();
+        let mut builder = DependencyBuilder::new(requirement.name).extras(requirement.extras);
+        if let Some(version) = version {
+            builder = builder.version(version);
+        }
+        if let Some(marker) = requirement.marker {
+            builder = builder.marker(marker);
+        }
+        if let Some(url) = requirement.url {
+            builder = builder.source(Source::from_reference(&url));
+        }
+        builder
+    }
+}
+
+/// Work out the group of a Poetry dependency table from its path.
+///  e.g. `tool.poetry.dependencies` -> `main`
+///  e.g. `tool.poetry.dev-dependencies` -> `dev`
+///  e.g. `tool.poetry.group.docs.dependencies` -> `docs`
+fn poetry_group(path: &[&str]) -> String {
+    match path {
+        [.., "group", group, "dependencies"] => group.to_string(),
+        [.., key] if key.ends_with("-dependencies") => {
+            key.trim_end_matches("-dependencies").to_string()
         }
+        _ => MAIN_GROUP.to_string(),
     }
 }
 
@@ -58,106 +253,538 @@ struct DependencyCollector {
 }
 
 impl DependencyCollector {
-    fn visit_table(&mut self, key: &str, table: &toml::value::Table) {
+    fn visit_table(&mut self, path: &[&str], table: &toml::value::Table) {
+        let key = path.last().copied().unwrap_or_default();
         // If the key contains "dependencies", then we are looking at a dependency table.
         if key.contains("dependencies") {
+            let group = poetry_group(path);
             for (dep_name, dep_value) in table {
-                self.visit_value(dep_name, dep_value);
+                self.visit_value(&group, dep_name, dep_value);
             }
         } else {
             for (k, v) in table {
                 if let toml::Value::Table(t) = v {
-                    self.visit_table(k, t);
+                    self.visit_table(&[path, &[k.as_str()]].concat(), t);
                 }
             }
         }
     }
 
-    fn visit_value(&mut self, key: &str, value: &toml::Value) {
-        match value {
+    fn visit_value(&mut self, group: &str, key: &str, value: &toml::Value) {
+        let builder = DependencyBuilder::new(key.to_string()).group(group.to_string());
+        let builder = match value {
             // For simple string values, assume it's the version directly
-            toml::Value::String(version) => {
-                self.dependencies.insert(
-                    DependencyBuilder::new(key.to_string())
-                        .version(version.clone())
-                        .build(),
+            toml::Value::String(version) => builder.version(version.clone()),
+            // e.g. `foo = { version = "^1.0", extras = ["bar"] }`, `foo = { git = "..." }`
+            toml::Value::Table(table) => poetry_constraint(builder, table),
+            // Multiple constraints, e.g. `foo = [{ version = "<2", python = "<3.8" }, { version = "^2", python = ">=3.8" }]`
+            toml::Value::Array(constraints) => {
+                let tables: Vec<&toml::value::Table> = constraints
+                    .iter()
+                    .filter_map(toml::Value::as_table)
+                    .collect();
+                let Some(first) = tables.first() else {
+                    return;
+                };
+                let versions: Vec<&str> = tables
+                    .iter()
+                    .filter_map(|table| table.get("version").and_then(toml::Value::as_str))
+                    .collect();
+                let builder = poetry_constraint(builder, first);
+                if versions.is_empty() {
+                    builder
+                } else {
+                    builder.version(versions.join(" || "))
+                }
+            }
+            _ => return,
+        };
+        self.dependencies.insert(builder.build());
+    }
+}
+
+/// Apply a single Poetry constraint table to a dependency.
+fn poetry_constraint(
+    mut builder: DependencyBuilder,
+    table: &toml::value::Table,
+) -> DependencyBuilder {
+    let get = |key: &str| table.get(key).and_then(toml::Value::as_str);
+
+    if let Some(version) = get("version") {
+        builder = builder.version(version.to_string());
+    }
+    if let Some(toml::Value::Array(extras)) = table.get("extras") {
+        builder = builder.extras(
+            extras
+                .iter()
+                .filter_map(toml::Value::as_str)
+                .map(ToString::to_string)
+                .collect(),
+        );
+    }
+    if let Some(marker) = get("markers") {
+        builder = builder.marker(marker.to_string());
+    }
+
+    if let Some(git) = get("git") {
+        // Keep the revision the repository is pinned to, the way pip would spell it.
+        let location = match get("rev").or(get("tag")).or(get("branch")) {
+            Some(reference) => format!("{}@{}", git, reference),
+            None => git.to_string(),
+        };
+        builder = builder.source(Source::Git(location));
+    } else if let Some(path) = get("path") {
+        builder = builder.source(Source::Path(path.to_string()));
+    } else if let Some(url) = get("url") {
+        builder = builder.source(Source::Url(url.to_string()));
+    }
+    builder
+}
+
+/// Strip a trailing comment, started by one of `prefixes`, from a line.
+/// A prefix only starts a comment at the beginning of the line or after whitespace,
+/// so URL fragments like `#egg=name` are kept.
+///  e.g. requirements files use `#`, `setup.cfg` uses `#` and `;`
+pub(crate) fn strip_comment<'a>(line: &'a str, prefixes: &[char]) -> &'a str {
+    line.char_indices()
+        .find(|&(idx, c)| {
+            prefixes.contains(&c) && (idx == 0 || line[..idx].ends_with(char::is_whitespace))
+        })
+        .map_or(line, |(idx, _)| &line[..idx])
+}
+
+/// Extract the project name from a pip-style URL or path requirement.
+///  e.g. `git+https://github.com/org/repo.git#egg=repo` -> `repo`
+///  e.g. `https://host/pkgs/foo_bar-1.0-py3-none-any.whl` -> `foo_bar`
+fn name_from_url(url: &str) -> Option<String> {
+    if let Some((_, fragment)) = url.split_once('#') {
+        return fragment
+            .split('&')
+            .find_map(|param| param.strip_prefix("egg="))
+            .map(ToString::to_string);
+    }
+
+    let file_name = url.rsplit('/').next()?;
+    if file_name.ends_with(".whl") || file_name.ends_with(".tar.gz") || file_name.ends_with(".zip")
+    {
+        return file_name
+            .split('-')
+            .next()
+            .filter(|name| !name.is_empty())
+            .map(ToString::to_string);
+    }
+    None
+}
+
+fn is_url_or_path(token: &str) -> bool {
+    token.contains("://") || token.starts_with('.') || token.starts_with('/')
+}
+
+/// Parse a single (already comment-stripped) requirements line.
+fn parse_pip_line(line: &str) -> Result<DependencyBuilder> {
+    // Per-requirement options such as `--hash=sha256:...` follow the requirement itself.
+    let line = line.find(" --").map_or(line, |idx| &line[..idx]).trim();
+
+    let url = line.split_whitespace().next().unwrap_or_default();
+    if is_url_or_path(url) {
+        let name = name_from_url(url)
+            .with_context(|| format!("could not determine the project name of `{}`", url))?;
+        return Ok(DependencyBuilder::new(name).source(Source::from_reference(url)));
+    }
+
+    let requirement = parse_requirement(line)?;
+    Ok(DependencyBuilder::from(requirement))
+}
+
+/// Join the lines of a requirements file that end with a `\` continuation,
+/// keeping the (1-based) number of the line each logical line starts on.
+fn logical_lines(content: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+
+    for (idx, line) in content.lines().enumerate() {
+        let (line_number, mut joined) = current.take().unwrap_or((idx + 1, String::new()));
+        match line.strip_suffix('\\') {
+            Some(continued) => {
+                joined.push_str(continued);
+                current = Some((line_number, joined));
+            }
+            None => {
+                joined.push_str(line);
+                lines.push((line_number, joined));
+            }
+        }
+    }
+    lines.extend(current);
+    lines
+}
+
+/// Split an option line into its flag and value.
+///  e.g. `-r base.txt`, `-rbase.txt` and `--requirement=base.txt` -> (`-r`, `base.txt`)
+fn split_option(line: &str) -> (&str, &str) {
+    const SHORT_OPTIONS: [&str; 3] = ["-r", "-c", "-e"];
+
+    if let Some((flag, value)) = line.split_once(|c: char| c == '=' || c.is_whitespace()) {
+        if flag.starts_with("--") || SHORT_OPTIONS.contains(&flag) {
+            return (flag, value.trim());
+        }
+    }
+    match SHORT_OPTIONS.iter().find(|flag| line.starts_with(*flag)) {
+        Some(flag) => (flag, line[flag.len()..].trim()),
+        None => (line, ""),
+    }
+}
+
+/// Work out the group of a requirements file from its name.
+///  e.g. `requirements.txt`, `requirements/base.txt` -> `main`
+///  e.g. `requirements-dev.txt`, `dev-requirements.txt`, `requirements/dev.txt` -> `dev`
+fn requirements_file_group(path: &Path) -> String {
+    const MAIN_NAMES: [&str; 5] = ["", "base", "common", "prod", "production"];
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let group = stem
+        .replace("requirements", "")
+        .trim_matches(|c: char| c == '-' || c == '_' || c == '.')
+        .to_string();
+
+    if MAIN_NAMES.contains(&group.as_str()) {
+        MAIN_GROUP.to_string()
+    } else {
+        group
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PipFileKind {
+    Requirements,
+    Constraints,
+}
+
+/// Collects the dependencies of a requirements file, following `-r` and `-c` includes.
+#[derive(Default)]
+struct PipCollector {
+    declarations: Vec<DependencyBuilder>,
+    /// Version specifiers from constraint files, keyed by package name.
+    constraints: HashMap<PackageName, String>,
+    /// The files currently being visited, used to detect include cycles.
+    stack: Vec<PathBuf>,
+    visited: HashSet<(PathBuf, bool)>,
+}
+
+impl PipCollector {
+    fn visit_file(&mut self, path: &Path, kind: PipFileKind) -> Result<()> {
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("Failed to read file at {:?}", path))?;
+
+        if self.stack.contains(&canonical) {
+            eprintln!("Skipping {:?}: it includes itself through `-r`/`-c`.", path);
+            return Ok(());
+        }
+        if !self
+            .visited
+            .insert((canonical.clone(), kind == PipFileKind::Constraints))
+        {
+            return Ok(());
+        }
+
+        let file_content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file at {:?}", path))?;
+
+        self.stack.push(canonical);
+        for (line_number, line) in logical_lines(&file_content) {
+            let line = strip_comment(&line, &['#']).trim().to_string();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Err(e) = self.visit_line(path, line_number, &line, kind) {
+                eprintln!(
+                    "Could not parse line {} of {:?} (`{}`): {:#}",
+                    line_number, path, line, e
                 );
             }
-            // For complex structures, look for a "version" key
-            toml::Value::Table(table) => {
-                if let Some(toml::Value::String(version)) = table.get("version") {
-                    self.dependencies.insert(
-                        DependencyBuilder::new(key.to_string())
-                            .version(version.clone())
-                            .build(),
-                    );
+        }
+        self.stack.pop();
+
+        Ok(())
+    }
+
+    fn visit_line(
+        &mut self,
+        path: &Path,
+        line_number: usize,
+        line: &str,
+        kind: PipFileKind,
+    ) -> Result<()> {
+        let include = |value: &str| path.parent().unwrap_or(Path::new("")).join(value);
+
+        if line.starts_with('-') {
+            match split_option(line) {
+                ("-r" | "--requirement", value) => self.visit_file(&include(value), kind)?,
+                ("-c" | "--constraint", value) => {
+                    self.visit_file(&include(value), PipFileKind::Constraints)?
                 }
+                ("-e" | "--editable", value) => {
+                    // Editable local paths (e.g. `-e .`) are project sources, not third-party
+                    // packages, unless they name themselves with an `#egg=` fragment.
+                    if let Some(name) = name_from_url(value) {
+                        let builder =
+                            DependencyBuilder::new(name).source(Source::from_reference(value));
+                        self.declare(builder, path, line_number, kind);
+                    }
+                }
+                // Other options (`--index-url`, `--find-links`, `--pre`, ...) only affect
+                // how pip installs packages.
+                _ => (),
             }
+            return Ok(());
+        }
 
-            _ => (),
+        let builder = parse_pip_line(line)?;
+        self.declare(builder, path, line_number, kind);
+        Ok(())
+    }
+
+    fn declare(&mut self, builder: DependencyBuilder, path: &Path, line: usize, kind: PipFileKind) {
+        match kind {
+            PipFileKind::Requirements => self.declarations.push(
+                builder
+                    .group(requirements_file_group(path))
+                    .origin(Origin::new(path.to_path_buf(), Some(line))),
+            ),
+            PipFileKind::Constraints => {
+                if let Some(version) = builder.version {
+                    self.constraints.insert(builder.id, version);
+                }
+            }
         }
     }
+
+    /// Constraints only pin the version of packages that are declared elsewhere.
+    fn finish(self) -> HashSet<Dependency> {
+        let constraints = self.constraints;
+        self.declarations
+            .into_iter()
+            .map(|builder| match constraints.get(&builder.id) {
+                Some(version) if builder.version.is_none() => builder.version(version.clone()),
+                _ => builder,
+            })
+            .map(DependencyBuilder::build)
+            .collect()
+    }
+}
+
+/// The constraint files a requirements file references with `-c`, relative to its directory.
+/// Unreadable files reference none, as reading them reports the error.
+pub fn get_constraint_files(requirements_file: &Path) -> Vec<PathBuf> {
+    let Ok(content) = fs::read_to_string(requirements_file) else {
+        return Vec::new();
+    };
+    let directory = requirements_file.parent().unwrap_or(Path::new(""));
+    logical_lines(&content)
+        .iter()
+        .map(|(_, line)| strip_comment(line, &['#']).trim())
+        .filter(|line| line.starts_with('-'))
+        .filter_map(|line| match split_option(line) {
+            ("-c" | "--constraint", value) => Some(directory.join(value)),
+            _ => None,
+        })
+        .collect()
 }
 
 fn get_pip_dependencies(dep_spec_file: &Path) -> Result<HashSet<Dependency>> {
-    let file_content = fs::read_to_string(dep_spec_file)
-        .with_context(|| format!("Failed to read file at {:?}", dep_spec_file))?;
-
-    let mut dependencies = HashSet::new();
-    for line in file_content.lines() {
-        let parts: Vec<&str> = line.split("==").collect();
-        if parts.len() == 2 {
-            dependencies.insert(
-                DependencyBuilder::new(parts[0].to_string())
-                    .version(parts[1].to_string())
-                    .build(),
-            );
-        }
+    let mut collector = PipCollector::default();
+    collector.visit_file(dep_spec_file, PipFileKind::Requirements)?;
+    Ok(collector.finish())
+}
+
+fn get_poetry_dependencies(pyproject: &toml::Value) -> HashSet<Dependency> {
+    let mut collector = DependencyCollector::default();
+
+    if let toml::Value::Table(table) = pyproject {
+        collector.visit_table(&[], table);
     }
 
-    Ok(dependencies)
+    collector.dependencies
 }
 
-fn get_poetry_dependencies(dep_spec_file: &Path) -> Result<HashSet<Dependency>> {
+/// Reads the Poetry tables, the PEP 621 `[project]` table and the PEP 735 `[dependency-groups]`
+/// table of a `pyproject.toml`.
+fn get_pyproject_dependencies(dep_spec_file: &Path) -> Result<HashSet<Dependency>> {
     let toml_str = fs::read_to_string(dep_spec_file)
         .with_context(|| format!("Failed to read TOML file at {:?}", dep_spec_file))?;
 
-    let toml_value: toml::Value =
+    let pyproject: toml::Value =
         toml::from_str(&toml_str).with_context(|| "Failed to parse TOML content")?;
 
-    let mut collector = DependencyCollector::default();
+    let mut dependencies = get_poetry_dependencies(&pyproject);
+    dependencies.extend(get_pep621_dependencies(&pyproject));
+    dependencies.extend(get_pep735_dependencies(&pyproject));
+    Ok(dependencies)
+}
+
+/// Work out which kind of dependency specification file `path` is from its name.
+pub fn spec_file_type(path: &Path) -> Option<DepType> {
+    let file_name = path.file_name()?.to_str()?;
+    match file_name {
+        "pyproject.toml" => Some(DepType::Poetry),
+        "Pipfile" => Some(DepType::Pipenv),
+        "setup.cfg" | "setup.py" => Some(DepType::Setuptools),
+        name if name.ends_with(".txt") => Some(DepType::Pip),
+        _ => None,
+    }
+}
+
+/// Reads the dependencies declared in a single specification file.
+/// Every dependency records the file as its origin, even if the format has no line information.
+fn get_file_dependencies(dep_spec_file: &Path) -> Result<Vec<Dependency>> {
+    let dependencies = match spec_file_type(dep_spec_file) {
+        Some(DepType::Pip) => get_pip_dependencies(dep_spec_file)?,
+        Some(DepType::Poetry) => get_pyproject_dependencies(dep_spec_file)?,
+        Some(DepType::Pipenv) => get_pipfile_dependencies(dep_spec_file)?,
+        Some(DepType::Setuptools) if dep_spec_file.ends_with("setup.py") => {
+            get_setup_py_dependencies(dep_spec_file)?
+        }
+        Some(DepType::Setuptools) => get_setup_cfg_dependencies(dep_spec_file)?,
+        None => bail!(
+            "{:?} is not a supported dependency specification file",
+            dep_spec_file
+        ),
+    };
+
+    let mut dependencies: Vec<Dependency> = dependencies
+        .into_iter()
+        .map(|mut dep| {
+            if dep.origins.is_empty() {
+                dep.origins
+                    .push(Origin::new(dep_spec_file.to_path_buf(), None));
+            }
+            dep
+        })
+        .collect();
+    // Keep the declaration order, so merging files is deterministic.
+    dependencies.sort_by_key(|dep| (dep.origin().and_then(Origin::line), dep.id.clone()));
+    Ok(dependencies)
+}
+
+/// A package declared with different versions in the same group by different files.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct VersionConflict {
+    id: PackageName,
+    group: String,
+    /// The conflicting declarations, each with the version and origin it was declared with.
+    declarations: Vec<Dependency>,
+}
+
+impl VersionConflict {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
 
-    if let toml::Value::Table(table) = toml_value {
-        collector.visit_table("", &table);
+    pub fn declarations(&self) -> &[Dependency] {
+        &self.declarations
     }
+}
+
+/// Merge the declarations of all files by package name and group.
+/// The first declaration wins; later ones add their origins, extras and, if the first one
+/// has none, their version.
+fn merge_dependencies(
+    declarations: Vec<Dependency>,
+) -> (HashSet<Dependency>, Vec<VersionConflict>) {
+    let mut merged: Vec<Dependency> = Vec::new();
+    let mut positions: HashMap<(PackageName, String), usize> = HashMap::new();
+    let mut versioned: BTreeMap<(PackageName, String), Vec<Dependency>> = BTreeMap::new();
+
+    for dep in declarations {
+        let key = (dep.id.clone(), dep.group.clone());
+        if dep.version.is_some() {
+            versioned.entry(key.clone()).or_default().push(dep.clone());
+        }
+        match positions.get(&key) {
+            Some(&idx) => {
+                let existing = &mut merged[idx];
+                if existing.version.is_none() {
+                    existing.version = dep.version;
+                }
+                for extra in dep.extras {
+                    if !existing.extras.contains(&extra) {
+                        existing.extras.push(extra);
+                    }
+                }
+                existing.origins.extend(dep.origins);
+            }
+            None => {
+                positions.insert(key, merged.len());
+                merged.push(dep);
+            }
+        }
+    }
+
+    let conflicts = versioned
+        .into_iter()
+        .filter(|(_, declarations)| {
+            let versions: HashSet<&str> = declarations.iter().map(Dependency::version).collect();
+            let files: HashSet<&Path> = declarations
+                .iter()
+                .flat_map(|dep| dep.origins.iter().map(Origin::file))
+                .collect();
+            versions.len() > 1 && files.len() > 1
+        })
+        .map(|((id, group), declarations)| VersionConflict {
+            id,
+            group,
+            declarations,
+        })
+        .collect();
 
-    Ok(collector.dependencies)
+    (merged.into_iter().collect(), conflicts)
 }
 
-/// This function reads a TOML file at the specified path and returns a HashSet of Dependency structs.
-pub fn get_dependencies(config: &Config) -> Result<HashSet<Dependency>> {
-    match config.dep_type {
-        DepType::Pip => get_pip_dependencies(&config.dep_spec_file),
-        DepType::Poetry => get_poetry_dependencies(&config.dep_spec_file),
+/// Reads the declarations of several dependency specification files.
+/// The files are discovered rather than picked by the user, so one that cannot be read, e.g. a
+/// Python 2 `setup.py`, is skipped with a warning. It is only an error when none can be read.
+fn read_spec_files(dep_spec_files: &[PathBuf]) -> Result<Vec<Dependency>> {
+    let mut declarations = Vec::new();
+    let mut first_error = None;
+    let mut read_any = false;
+    for dep_spec_file in dep_spec_files {
+        match get_file_dependencies(dep_spec_file) {
+            Ok(dependencies) => {
+                declarations.extend(dependencies);
+                read_any = true;
+            }
+            Err(e) => {
+                eprintln!("Skipping {:?}: {:#}", dep_spec_file, e);
+                first_error.get_or_insert(e);
+            }
+        }
     }
+    match first_error {
+        Some(e) if !read_any => Err(e),
+        _ => Ok(declarations),
+    }
+}
+
+/// Reads every dependency specification file of the project and merges their declarations.
+pub fn get_dependencies(config: &Config) -> Result<(HashSet<Dependency>, Vec<VersionConflict>)> {
+    let declarations = read_spec_files(&config.dep_spec_files)?;
+    Ok(merge_dependencies(declarations))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
-    use std::io::Write;
-    use std::path::PathBuf;
+    use crate::project_assets::test_utils::create_file;
     use tempfile::tempdir;
-    // Requirements.txt still need tested
-
-    /// Helper function to create a temporary pyproject.toml file.
-    fn create_pyproject_toml_file(dir: &tempfile::TempDir, content: &str) -> PathBuf {
-        let file_path = dir.path().join("pyproject.toml");
-        let mut file = File::create(&file_path).expect("Failed to create file.");
-        writeln!(file, "{}", content).expect("Failed to write to file.");
-        file_path
-    }
 
     /// Tests the basic functionality of DependencyBuilder and Dependency structs.
     #[test]
@@ -166,7 +793,8 @@ mod tests {
             .version("1.0.0".to_string())
             .build();
 
-        assert_eq!(dep.id, "my_dep");
+        // The id is normalized as per PEP 503.
+        assert_eq!(dep.id, "my-dep");
         assert_eq!(dep.version, Some("1.0.0".to_string()));
     }
 
@@ -174,8 +802,9 @@ mod tests {
     #[test]
     fn parse_simple_dependencies() {
         let temp_dir = tempdir().unwrap();
-        let toml_path = create_pyproject_toml_file(
+        let toml_path = create_file(
             &temp_dir,
+            "pyproject.toml",
             "
             [tool.poetry.dependencies]
             python = \"^3.8\"
@@ -185,26 +814,30 @@ mod tests {
         );
 
         let dependencies =
-            get_poetry_dependencies(toml_path.as_path()).expect("Failed to get dependencies");
-
-        assert!(dependencies.contains(&Dependency {
-            id: "package_a".to_string(),
-            version: Some("^1.0".to_string()),
-        }));
-        assert!(dependencies.contains(&Dependency {
-            id: "package_b".to_string(),
-            version: Some("^2.0".to_string()),
-        }));
+            get_pyproject_dependencies(toml_path.as_path()).expect("Failed to get dependencies");
+
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("package_a".to_string())
+                .version("^1.0".to_string())
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("package_b".to_string())
+                .version("^2.0".to_string())
+                .build()
+        ));
         // Including the Python version as a dependency for completeness.
-        assert!(dependencies.contains(&Dependency {
-            id: "python".to_string(),
-            version: Some("^3.8".to_string()),
-        }));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("python".to_string())
+                .version("^3.8".to_string())
+                .build()
+        ));
         assert_eq!(dependencies.len(), 3);
 
         // Test different categories such as dev-dependencies, build-dependencies, etc.
-        let toml_path = create_pyproject_toml_file(
+        let toml_path = create_file(
             &temp_dir,
+            "pyproject.toml",
             "
             [tool.poetry.dev-dependencies]
             package_c = \"^3.0\"
@@ -213,20 +846,25 @@ mod tests {
         );
 
         let dependencies =
-            get_poetry_dependencies(toml_path.as_path()).expect("Failed to get dependencies");
+            get_pyproject_dependencies(toml_path.as_path()).expect("Failed to get dependencies");
 
-        assert!(dependencies.contains(&Dependency {
-            id: "package_c".to_string(),
-            version: Some("^3.0".to_string()),
-        }));
-        assert!(dependencies.contains(&Dependency {
-            id: "package_d".to_string(),
-            version: Some("^4.0".to_string()),
-        }));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("package_c".to_string())
+                .version("^3.0".to_string())
+                .group("dev".to_string())
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("package_d".to_string())
+                .version("^4.0".to_string())
+                .group("dev".to_string())
+                .build()
+        ));
 
         // Test categories that are not dependencies.
-        let toml_path = create_pyproject_toml_file(
+        let toml_path = create_file(
             &temp_dir,
+            "pyproject.toml",
             "
             [tool.poetry]
             name = \"my_project\"
@@ -236,7 +874,7 @@ mod tests {
         );
 
         let dependencies =
-            get_poetry_dependencies(toml_path.as_path()).expect("Failed to get dependencies");
+            get_pyproject_dependencies(toml_path.as_path()).expect("Failed to get dependencies");
 
         assert!(dependencies.is_empty());
     }
@@ -247,8 +885,9 @@ mod tests {
 
         // Test a more complex TOML dependency file.
         // Ex: fastapi = { version = "^0.109.2", optional = true }
-        let toml_path = create_pyproject_toml_file(
+        let toml_path = create_file(
             &temp_dir,
+            "pyproject.toml",
             "
             [tool.poetry.dependencies]
             fastapi = { version = \"^0.109.2\", optional = true }
@@ -258,31 +897,512 @@ mod tests {
         );
 
         let dependencies =
-            get_poetry_dependencies(toml_path.as_path()).expect("Failed to get dependencies");
+            get_pyproject_dependencies(toml_path.as_path()).expect("Failed to get dependencies");
 
-        assert!(dependencies.contains(&Dependency {
-            id: "fastapi".to_string(),
-            version: Some("^0.109.2".to_string()),
-        }));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("fastapi".to_string())
+                .version("^0.109.2".to_string())
+                .build()
+        ));
 
-        assert!(dependencies.contains(&Dependency {
-            id: "mkdocs-material".to_string(),
-            version: Some("^9.5.9".to_string()),
-        }));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("mkdocs-material".to_string())
+                .version("^9.5.9".to_string())
+                .extras(vec!["imaging".to_string()])
+                .build()
+        ));
 
-        assert!(dependencies.contains(&Dependency {
-            id: "uvicorn".to_string(),
-            version: Some("^0.13.4".to_string()),
-        }));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("uvicorn".to_string())
+                .version("^0.13.4".to_string())
+                .build()
+        ));
+    }
+
+    /// Dependencies installed from somewhere other than the package index are kept,
+    /// along with where they come from.
+    #[test]
+    fn test_source_dependencies() {
+        let temp_dir = tempdir().unwrap();
+        let toml_path = create_file(
+            &temp_dir,
+            "pyproject.toml",
+            "
+            [tool.poetry.dependencies]
+            foo = { git = \"https://github.com/org/foo.git\", tag = \"v1.0\" }
+            bar = { path = \"../bar\", develop = true }
+            baz = { url = \"https://host/pkgs/baz-1.0.tar.gz\" }
+            numpy = [
+                { version = \"<1.25\", python = \"<3.9\" },
+                { version = \"^1.26\", python = \">=3.9\" },
+            ]
+            unknown = 42
+                        ",
+        );
+
+        let dependencies =
+            get_pyproject_dependencies(toml_path.as_path()).expect("Failed to get dependencies");
+
+        assert_eq!(dependencies.len(), 4);
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("foo".to_string())
+                .source(Source::Git(
+                    "https://github.com/org/foo.git@v1.0".to_string()
+                ))
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("bar".to_string())
+                .source(Source::Path("../bar".to_string()))
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("baz".to_string())
+                .source(Source::Url("https://host/pkgs/baz-1.0.tar.gz".to_string()))
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("numpy".to_string())
+                .version("<1.25 || ^1.26".to_string())
+                .build()
+        ));
+    }
+
+    #[test]
+    fn test_source_from_reference() {
+        assert_eq!(
+            Source::from_reference("git+https://github.com/org/repo.git"),
+            Source::Git("git+https://github.com/org/repo.git".to_string())
+        );
+        assert_eq!(
+            Source::from_reference("./vendor/foo"),
+            Source::Path("./vendor/foo".to_string())
+        );
+        assert_eq!(
+            Source::from_reference("file:///tmp/foo-1.0.whl"),
+            Source::Path("file:///tmp/foo-1.0.whl".to_string())
+        );
+        assert_eq!(
+            Source::from_reference("https://host/foo-1.0.zip"),
+            Source::Url("https://host/foo-1.0.zip".to_string())
+        );
     }
 
     /// Tests invalid TOML content.
     #[test]
     fn test_invalid_toml() {
         let temp_dir = tempdir().unwrap();
-        let toml_path = create_pyproject_toml_file(&temp_dir, "invalid toml content");
+        let toml_path = create_file(&temp_dir, "pyproject.toml", "invalid toml content");
 
-        let result = get_poetry_dependencies(toml_path.as_path());
+        let result = get_pyproject_dependencies(toml_path.as_path());
         assert!(result.is_err());
     }
+
+    /// Declarations from several files are merged by name and group, keeping every origin.
+    #[test]
+    fn test_merge_spec_files() {
+        let temp_dir = tempdir().unwrap();
+        let pyproject = create_file(
+            &temp_dir,
+            "pyproject.toml",
+            "
+            [project]
+            name = \"my_project\"
+            dependencies = [\"requests>=2.31\", \"Rich\"]
+                        ",
+        );
+        let requirements = temp_dir.path().join("requirements.txt");
+        fs::write(&requirements, "requests==2.30.0\nrich==13.7.1\nclick\n").unwrap();
+        let requirements_dev = temp_dir.path().join("requirements-dev.txt");
+        fs::write(&requirements_dev, "requests==2.30.0\n").unwrap();
+        let setup_py = temp_dir.path().join("setup.py");
+        fs::write(
+            &setup_py,
+            "import setuptools\nsetuptools.setup(install_requires=('click',))\n",
+        )
+        .unwrap();
+
+        let declarations = read_spec_files(&[
+            pyproject.clone(),
+            requirements.clone(),
+            requirements_dev,
+            setup_py,
+        ])
+        .expect("Failed to get dependencies");
+        let (dependencies, conflicts) = merge_dependencies(declarations);
+
+        assert_eq!(dependencies.len(), 4);
+        let find = |id: &str, group: &str| {
+            dependencies
+                .iter()
+                .find(|dep| dep.id() == id && dep.group() == group)
+                .expect("Dependency not found")
+        };
+
+        // The first declaration's version is kept, and an unpinned one picks up a pin.
+        let requests = find("requests", MAIN_GROUP);
+        assert_eq!(requests.version(), ">=2.31");
+        assert_eq!(
+            requests.origins(),
+            [
+                Origin::new(pyproject.clone(), None),
+                Origin::new(requirements.clone(), Some(1))
+            ]
+        );
+        assert_eq!(find("rich", MAIN_GROUP).version(), "==13.7.1");
+        assert_eq!(find("requests", "dev").origins().len(), 1);
+        assert_eq!(find("click", MAIN_GROUP).origins().len(), 2);
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].id(), "requests");
+        assert_eq!(conflicts[0].group(), MAIN_GROUP);
+        let versions: Vec<&str> = conflicts[0]
+            .declarations()
+            .iter()
+            .map(Dependency::version)
+            .collect();
+        assert_eq!(versions, vec![">=2.31", "==2.30.0"]);
+    }
+
+    /// Both files are read when a project splits its configuration between them.
+    #[test]
+    fn parse_setup_cfg_and_setup_py() {
+        let temp_dir = tempdir().unwrap();
+        let setup_cfg = temp_dir.path().join("setup.cfg");
+        fs::write(&setup_cfg, "[options]\ninstall_requires = requests\n").unwrap();
+        let setup_py = temp_dir.path().join("setup.py");
+        fs::write(
+            &setup_py,
+            "import setuptools\nsetuptools.setup(install_requires=('click',))\n",
+        )
+        .unwrap();
+
+        let dependencies =
+            read_spec_files(&[setup_cfg, setup_py]).expect("Failed to get dependencies");
+
+        let ids: HashSet<&str> = dependencies.iter().map(|dep| dep.id()).collect();
+        assert_eq!(ids, HashSet::from(["requests", "click"]));
+    }
+
+    /// A file that cannot be read does not hide the declarations of the others.
+    #[test]
+    fn skip_unreadable_spec_files() {
+        let temp_dir = tempdir().unwrap();
+        let pyproject = create_file(
+            &temp_dir,
+            "pyproject.toml",
+            "
+            [project]
+            name = \"my_project\"
+            dependencies = [\"requests\"]
+                        ",
+        );
+        // A Python 2 setup.py the parser rejects.
+        let setup_py = temp_dir.path().join("setup.py");
+        fs::write(
+            &setup_py,
+            "from setuptools import setup\nprint \"building\"\nsetup(install_requires=['click'])\n",
+        )
+        .unwrap();
+
+        let dependencies =
+            read_spec_files(&[pyproject, setup_py.clone()]).expect("Failed to get dependencies");
+        let ids: Vec<&str> = dependencies.iter().map(|dep| dep.id()).collect();
+        assert_eq!(ids, vec!["requests"]);
+
+        // It is still an error when no file can be read.
+        assert!(read_spec_files(&[setup_py]).is_err());
+    }
+
+    #[test]
+    fn test_spec_file_type() {
+        assert_eq!(
+            spec_file_type(Path::new("pyproject.toml")),
+            Some(DepType::Poetry)
+        );
+        assert_eq!(
+            spec_file_type(Path::new("requirements/dev.txt")),
+            Some(DepType::Pip)
+        );
+        assert_eq!(
+            spec_file_type(Path::new("setup.py")),
+            Some(DepType::Setuptools)
+        );
+        assert!(spec_file_type(Path::new("README.md")).is_none());
+    }
+
+    #[test]
+    fn test_strip_comment() {
+        assert_eq!(strip_comment("# a comment", &['#']), "");
+        assert_eq!(
+            strip_comment("requests==2.31.0  # pinned", &['#']),
+            "requests==2.31.0  "
+        );
+        assert_eq!(
+            strip_comment("git+https://github.com/org/repo.git#egg=repo", &['#']),
+            "git+https://github.com/org/repo.git#egg=repo"
+        );
+        assert_eq!(
+            strip_comment("rich  ; pretty output", &['#', ';']),
+            "rich  "
+        );
+    }
+
+    #[test]
+    fn test_name_from_url() {
+        assert_eq!(
+            name_from_url("git+https://github.com/org/repo.git#egg=repo"),
+            Some("repo".to_string())
+        );
+        assert_eq!(
+            name_from_url("https://host/pkgs/foo_bar-1.0-py3-none-any.whl"),
+            Some("foo_bar".to_string())
+        );
+        assert_eq!(name_from_url("git+https://github.com/org/repo.git"), None);
+    }
+
+    #[test]
+    fn parse_pip_dependencies() {
+        let temp_dir = tempdir().unwrap();
+        let requirements_path = create_file(
+            &temp_dir,
+            "requirements.txt",
+            "
+            # Pinned
+            requests==2.31.0
+            Django>=4.2,<5.0  # LTS
+            attrs~=23.1
+            uvicorn[standard] != 0.29.0
+            typing_extensions ; python_version < \"3.11\"
+            rich
+            pip @ https://github.com/pypa/pip/archive/1.3.1.zip
+            git+https://github.com/org/repo.git#egg=repo
+            --index-url https://pypi.org/simple
+            ",
+        );
+
+        let dependencies =
+            get_pip_dependencies(requirements_path.as_path()).expect("Failed to get dependencies");
+        let origin = |line| Origin::new(requirements_path.clone(), Some(line));
+
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("requests".to_string())
+                .version("==2.31.0".to_string())
+                .origin(origin(3))
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("Django".to_string())
+                .version(">=4.2,<5.0".to_string())
+                .origin(origin(4))
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("attrs".to_string())
+                .version("~=23.1".to_string())
+                .origin(origin(5))
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("uvicorn".to_string())
+                .version("!=0.29.0".to_string())
+                .extras(vec!["standard".to_string()])
+                .origin(origin(6))
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("typing_extensions".to_string())
+                .marker("python_version < \"3.11\"".to_string())
+                .origin(origin(7))
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("rich".to_string())
+                .origin(origin(8))
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("pip".to_string())
+                .source(Source::Url(
+                    "https://github.com/pypa/pip/archive/1.3.1.zip".to_string()
+                ))
+                .origin(origin(9))
+                .build()
+        ));
+        assert!(dependencies.contains(
+            &DependencyBuilder::new("repo".to_string())
+                .source(Source::Git(
+                    "git+https://github.com/org/repo.git#egg=repo".to_string()
+                ))
+                .origin(origin(10))
+                .build()
+        ));
+        assert_eq!(dependencies.len(), 8);
+    }
+
+    /// Lines that cannot be understood are reported and skipped, without failing the whole file.
+    #[test]
+    fn parse_pip_dependencies_with_invalid_lines() {
+        let temp_dir = tempdir().unwrap();
+        let requirements_path = create_file(
+            &temp_dir,
+            "requirements.txt",
+            "
+            requests==2.31.0
+            this is not a requirement
+            >=1.0
+            ",
+        );
+
+        let dependencies =
+            get_pip_dependencies(requirements_path.as_path()).expect("Failed to get dependencies");
+
+        assert_eq!(dependencies.len(), 1);
+        assert!(parse_pip_line("this is not a requirement").is_err());
+    }
+
+    #[test]
+    fn test_logical_lines() {
+        let lines = logical_lines("requests \\\n    ==2.31.0\nrich\n");
+        assert_eq!(
+            lines,
+            vec![
+                (1, "requests     ==2.31.0".to_string()),
+                (3, "rich".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_option() {
+        assert_eq!(split_option("-r base.txt"), ("-r", "base.txt"));
+        assert_eq!(split_option("-rbase.txt"), ("-r", "base.txt"));
+        assert_eq!(
+            split_option("--requirement=base.txt"),
+            ("--requirement", "base.txt")
+        );
+        assert_eq!(split_option("-e ."), ("-e", "."));
+        assert_eq!(split_option("--pre"), ("--pre", ""));
+    }
+
+    /// Tests `-r` includes, `-c` constraints, `-e` editables and per-requirement options.
+    #[test]
+    fn parse_pip_dependencies_with_includes() {
+        let temp_dir = tempdir().unwrap();
+        let requirements_dir = temp_dir.path().join("requirements");
+        fs::create_dir(&requirements_dir).unwrap();
+        fs::write(
+            requirements_dir.join("base.txt"),
+            "-c ../constraints.txt\nrequests\nattrs>=23 \\\n    --hash=sha256:abc\n",
+        )
+        .unwrap();
+        fs::write(
+            requirements_dir.join("dev.txt"),
+            "--index-url https://pypi.org/simple\n-r base.txt\n-e .\n-e git+https://github.com/org/tool.git#egg=tool\npytest\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("constraints.txt"),
+            "requests==2.31.0\nattrs==23.2.0\nnumpy==1.26.4\n",
+        )
+        .unwrap();
+
+        let dev_path = requirements_dir.join("dev.txt");
+        let dependencies =
+            get_pip_dependencies(dev_path.as_path()).expect("Failed to get dependencies");
+
+        let ids: HashSet<&str> = dependencies.iter().map(|dep| dep.id()).collect();
+        assert_eq!(ids, HashSet::from(["requests", "attrs", "tool", "pytest"]));
+
+        // Constraints fill in the version of declared packages only.
+        let requests = dependencies.iter().find(|d| d.id() == "requests").unwrap();
+        assert_eq!(requests.version(), "==2.31.0");
+        let attrs = dependencies.iter().find(|d| d.id() == "attrs").unwrap();
+        assert_eq!(attrs.version(), ">=23");
+
+        // Each dependency remembers where it was declared.
+        let origin = requests.origin().unwrap();
+        assert_eq!(origin.file(), requirements_dir.join("base.txt"));
+        assert_eq!(origin.line(), Some(2));
+        let pytest = dependencies.iter().find(|d| d.id() == "pytest").unwrap();
+        assert_eq!(pytest.origin().unwrap().file(), dev_path);
+        assert_eq!(pytest.origin().unwrap().line(), Some(5));
+    }
+
+    #[test]
+    fn parse_pip_dependencies_with_include_cycle() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "-r b.txt\nrequests\n").unwrap();
+        fs::write(temp_dir.path().join("b.txt"), "-r a.txt\nrich\n").unwrap();
+
+        let dependencies = get_pip_dependencies(temp_dir.path().join("a.txt").as_path())
+            .expect("Failed to get dependencies");
+
+        let ids: HashSet<&str> = dependencies.iter().map(|dep| dep.id()).collect();
+        assert_eq!(ids, HashSet::from(["requests", "rich"]));
+    }
+
+    #[test]
+    fn parse_pip_dependencies_missing_file() {
+        let temp_dir = tempdir().unwrap();
+        let result = get_pip_dependencies(temp_dir.path().join("requirements.txt").as_path());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_poetry_group() {
+        assert_eq!(
+            poetry_group(&["tool", "poetry", "dependencies"]),
+            MAIN_GROUP
+        );
+        assert_eq!(poetry_group(&["tool", "poetry", "dev-dependencies"]), "dev");
+        assert_eq!(
+            poetry_group(&["tool", "poetry", "group", "docs", "dependencies"]),
+            "docs"
+        );
+    }
+
+    #[test]
+    fn test_requirements_file_group() {
+        let group = |path: &str| requirements_file_group(Path::new(path));
+        assert_eq!(group("requirements.txt"), MAIN_GROUP);
+        assert_eq!(group("requirements/base.txt"), MAIN_GROUP);
+        assert_eq!(group("requirements/prod.txt"), MAIN_GROUP);
+        assert_eq!(group("requirements-dev.txt"), "dev");
+        assert_eq!(group("dev-requirements.txt"), "dev");
+        assert_eq!(group("requirements/test.txt"), "test");
+        assert_eq!(group("requirements_docs.txt"), "docs");
+    }
+
+    #[test]
+    fn parse_poetry_groups() {
+        let temp_dir = tempdir().unwrap();
+        let toml_path = create_file(
+            &temp_dir,
+            "pyproject.toml",
+            "
+            [tool.poetry.dependencies]
+            fastapi = \"^0.109.2\"
+
+            [tool.poetry.dev-dependencies]
+            black = \"^24.0\"
+
+            [tool.poetry.group.test.dependencies]
+            pytest = \"^8.0\"
+                        ",
+        );
+
+        let dependencies =
+            get_pyproject_dependencies(toml_path.as_path()).expect("Failed to get dependencies");
+
+        let mut groups: Vec<(&str, &str)> = dependencies
+            .iter()
+            .map(|dep| (dep.group(), dep.id()))
+            .collect();
+        groups.sort();
+        assert_eq!(
+            groups,
+            vec![("dev", "black"), ("main", "fastapi"), ("test", "pytest")]
+        );
+    }
 }
@@ -0,0 +1,185 @@
+use std::str;
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, bail, Result};
+use regex::bytes::Regex;
+
+/// The byte order mark some editors start UTF-8 files with.
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// The characters of Windows-1252 for the bytes `0x80` to `0x9f`, where it differs from Latin-1.
+/// The five bytes it leaves undefined are replaced.
+const CP1252_HIGH: [char; 32] = [
+    '€', '\u{fffd}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{fffd}', 'Ž',
+    '\u{fffd}', '\u{fffd}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{fffd}',
+    'ž', 'Ÿ',
+];
+
+/// Code pages whose characters take several bytes, some of them in the ASCII range, so they are
+/// not safe to read as ASCII.
+const MULTI_BYTE_CODE_PAGES: [&str; 5] = ["cp932", "cp936", "cp949", "cp950", "cp1361"];
+
+/// EBCDIC code pages, which do not extend ASCII at all.
+const EBCDIC_CODE_PAGES: [&str; 7] = [
+    "cp037", "cp273", "cp424", "cp500", "cp875", "cp1026", "cp1140",
+];
+
+/// How the bytes of a source file are turned into text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Utf8,
+    Ascii,
+    Latin1,
+    Cp1252,
+    /// Another single-byte encoding that extends ASCII, e.g. `iso-8859-15` or `koi8-r`.
+    /// Its non-ASCII characters are replaced, as only ASCII matters to find imports.
+    AsciiCompatible,
+}
+
+impl Encoding {
+    /// The encoding of a PEP 263 name, normalized like Python does, e.g. `Latin_1` -> `latin1`
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase().replace(['-', '_'], "");
+        match name.as_str() {
+            "utf8" | "utf8sig" | "u8" | "cp65001" => Some(Self::Utf8),
+            "ascii" | "usascii" | "646" => Some(Self::Ascii),
+            "latin1" | "latin" | "l1" | "iso88591" | "iso885911987" | "8859" | "cp819" => {
+                Some(Self::Latin1)
+            }
+            "cp1252" | "windows1252" => Some(Self::Cp1252),
+            _ if MULTI_BYTE_CODE_PAGES.contains(&name.as_str()) => None,
+            _ if EBCDIC_CODE_PAGES.contains(&name.as_str()) => None,
+            _ if ["iso8859", "latin", "cp", "windows", "koi8", "mac"]
+                .iter()
+                .any(|prefix| name.starts_with(prefix)) =>
+            {
+                Some(Self::AsciiCompatible)
+            }
+            _ => None,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> Result<String> {
+        match self {
+            Self::Utf8 => str::from_utf8(bytes)
+                .map(str::to_string)
+                .map_err(|e| anyhow!("invalid UTF-8: {}", e)),
+            Self::Ascii => match bytes.iter().position(|byte| !byte.is_ascii()) {
+                Some(offset) => bail!("invalid ASCII: non-ASCII byte at offset {}", offset),
+                None => Ok(bytes.iter().map(|byte| *byte as char).collect()),
+            },
+            Self::Latin1 => Ok(bytes.iter().map(|byte| *byte as char).collect()),
+            Self::Cp1252 => Ok(bytes
+                .iter()
+                .map(|byte| match byte {
+                    0x80..=0x9f => CP1252_HIGH[(byte - 0x80) as usize],
+                    _ => *byte as char,
+                })
+                .collect()),
+            Self::AsciiCompatible => Ok(bytes
+                .iter()
+                .map(|byte| {
+                    if byte.is_ascii() {
+                        *byte as char
+                    } else {
+                        '\u{fffd}'
+                    }
+                })
+                .collect()),
+        }
+    }
+}
+
+/// The encoding declared by a PEP 263 coding cookie, e.g. `# -*- coding: latin-1 -*-`, on the
+/// first line or on the second one after a comment or blank line.
+fn coding_cookie(bytes: &[u8]) -> Option<String> {
+    static COOKIE: OnceLock<Regex> = OnceLock::new();
+    static BLANK_OR_COMMENT: OnceLock<Regex> = OnceLock::new();
+    let cookie = COOKIE.get_or_init(|| {
+        Regex::new(r"^[ \t\f]*#.*?coding[:=][ \t]*([-_.a-zA-Z0-9]+)").expect("Invalid regex")
+    });
+    let blank_or_comment =
+        BLANK_OR_COMMENT.get_or_init(|| Regex::new(r"^[ \t\f]*(#.*)?\r?$").expect("Invalid regex"));
+
+    let mut lines = bytes.split(|byte| *byte == b'\n');
+    let first = lines.next().unwrap_or_default();
+    let second = lines
+        .next()
+        .filter(|_| blank_or_comment.is_match(first))
+        .unwrap_or_default();
+    [first, second].iter().find_map(|line| {
+        cookie
+            .captures(line)
+            .map(|captures| String::from_utf8_lossy(&captures[1]).to_string())
+    })
+}
+
+/// Decodes the bytes of a Python source, honouring a UTF-8 byte order mark and PEP 263 coding
+/// cookies. Sources are UTF-8 otherwise.
+pub fn decode_source(bytes: &[u8]) -> Result<String> {
+    if let Some(bytes) = bytes.strip_prefix(UTF8_BOM) {
+        return match coding_cookie(bytes) {
+            Some(name) if Encoding::from_name(&name) != Some(Encoding::Utf8) => {
+                bail!("encoding `{}` declared after a UTF-8 byte order mark", name)
+            }
+            _ => Encoding::Utf8.decode(bytes),
+        };
+    }
+
+    let encoding = match &coding_cookie(bytes) {
+        Some(name) => {
+            Encoding::from_name(name).ok_or_else(|| anyhow!("unsupported encoding `{}`", name))?
+        }
+        None => Encoding::Utf8,
+    };
+    encoding.decode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coding_cookie() {
+        assert_eq!(
+            coding_cookie(b"# -*- coding: latin-1 -*-\nimport os\n"),
+            Some("latin-1".to_string())
+        );
+        assert_eq!(
+            coding_cookie(b"#!/usr/bin/env python\n# vim: set fileencoding=cp1252 :\n"),
+            Some("cp1252".to_string())
+        );
+        // The cookie must be on one of the first two lines, in a comment.
+        assert_eq!(coding_cookie(b"import os\n# coding: latin-1\n"), None);
+        assert_eq!(coding_cookie(b"\n\n# coding: latin-1\n"), None);
+        assert_eq!(coding_cookie(b"x = 'coding: latin-1'\n"), None);
+    }
+
+    #[test]
+    fn test_decode_source() {
+        assert_eq!(
+            decode_source(b"# coding: latin-1\n# caf\xe9\n").unwrap(),
+            "# coding: latin-1\n# caf\u{e9}\n"
+        );
+        assert_eq!(
+            decode_source(b"# coding: cp1252\nx = '\x80'\n").unwrap(),
+            "# coding: cp1252\nx = '\u{20ac}'\n"
+        );
+        assert_eq!(
+            decode_source(b"# coding: iso-8859-15\nimport os  # \xa4\n").unwrap(),
+            "# coding: iso-8859-15\nimport os  # \u{fffd}\n"
+        );
+        assert_eq!(
+            decode_source(b"\xef\xbb\xbfimport os\n").unwrap(),
+            "import os\n"
+        );
+
+        // Sources that cannot be decoded are errors, never silently dropped.
+        assert!(decode_source(b"# caf\xe9\nimport os\n").is_err());
+        assert!(decode_source(b"# coding: ascii\n# caf\xe9\n").is_err());
+        assert!(decode_source(b"# coding: shift_jis\nimport os\n").is_err());
+        assert!(decode_source(b"# coding: cp500\nimport os\n").is_err());
+        assert!(decode_source(b"# coding: cp1140\nimport os\n").is_err());
+        assert!(decode_source(b"\xef\xbb\xbf# coding: latin-1\n").is_err());
+    }
+}
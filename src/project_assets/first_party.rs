@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::config::Config;
+
+/// The extensions of the files that are importable modules: Python sources and stubs, and Cython
+/// sources and declarations.
+const MODULE_EXTENSIONS: [&str; 4] = ["py", "pyi", "pyx", "pxd"];
+
+/// Modules at the root of a project that run it rather than being imported by it.
+const ROOT_SCRIPTS: [&str; 6] = ["setup", "conftest", "manage", "noxfile", "fabfile", "dodo"];
+
+/// A directory is a regular package if it holds an `__init__.py`.
+fn is_package(dir: &Path) -> bool {
+    dir.join("__init__.py").is_file()
+}
+
+/// The top-level packages and modules directly under `dir`.
+///  e.g. `myapp/__init__.py` -> `myapp`, and with `modules`, `cli.py` -> `cli`, the stub
+///  `typed.pyi` -> `typed` or the Cython module `fast.pyx` -> `fast`
+fn top_level_names(dir: &Path, modules: bool) -> HashSet<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return HashSet::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let name = if path.is_dir() && is_package(&path) {
+                path.file_name()
+            } else if modules
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| MODULE_EXTENSIONS.contains(&ext))
+            {
+                path.file_stem()
+            } else {
+                None
+            };
+            name.map(|name| name.to_string_lossy().to_string())
+        })
+        .collect()
+}
+
+/// The import name of a project name, e.g. `my-project` -> `my_project`
+fn import_name(project_name: &str) -> String {
+    project_name.trim().replace(['-', '.'], "_").to_lowercase()
+}
+
+/// The last component of a package path, e.g. `src/myapp` -> `myapp`
+fn last_component(path: &str) -> Option<String> {
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .map(|name| name.trim_end_matches(".py").to_string())
+}
+
+/// Reads the names of the packages a `pyproject.toml` declares for itself.
+fn get_declared_names(pyproject: &Path) -> Result<HashSet<String>> {
+    let toml_str = fs::read_to_string(pyproject)
+        .with_context(|| format!("Failed to read TOML file at {:?}", pyproject))?;
+
+    let toml_value: toml::Value =
+        toml::from_str(&toml_str).with_context(|| "Failed to parse TOML content")?;
+
+    let mut names = HashSet::new();
+    let get = |path: &[&str]| {
+        path.iter()
+            .try_fold(&toml_value, |value, key| value.get(*key))
+    };
+    let strings = |value: Option<&toml::Value>| -> Vec<String> {
+        value
+            .and_then(toml::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(toml::Value::as_str)
+            .map(ToString::to_string)
+            .collect()
+    };
+
+    // The distribution itself, e.g. `[project] name = "my-project"`
+    for path in [["project", "name"].as_slice(), &["tool", "poetry", "name"]] {
+        if let Some(name) = get(path).and_then(toml::Value::as_str) {
+            names.insert(import_name(name));
+        }
+    }
+
+    // e.g. `[tool.poetry] packages = [{ include = "myapp", from = "src" }]`
+    if let Some(packages) = get(&["tool", "poetry", "packages"]).and_then(toml::Value::as_array) {
+        names.extend(
+            packages
+                .iter()
+                .filter_map(|package| package.get("include"))
+                .filter_map(toml::Value::as_str)
+                .filter_map(last_component),
+        );
+    }
+
+    // e.g. `[tool.setuptools] packages = ["myapp", "myapp.sub"]`, `py-modules = ["cli"]`
+    for key in ["packages", "py-modules"] {
+        for package in strings(get(&["tool", "setuptools", key])) {
+            names.insert(package.split('.').next().unwrap_or_default().to_string());
+        }
+    }
+
+    // e.g. `[tool.hatch.build.targets.wheel] packages = ["src/myapp"]`
+    let hatch_packages = get(&["tool", "hatch", "build", "targets", "wheel", "packages"]);
+    names.extend(
+        strings(hatch_packages)
+            .iter()
+            .filter_map(|package| last_component(package)),
+    );
+
+    names.retain(|name| !name.is_empty());
+    Ok(names)
+}
+
+/// Works out the top-level modules that belong to the project itself, so they are not mistaken
+/// for third-party imports: packages and modules at the root of the project, but for scripts
+/// such as `setup.py`, or under `src/`, and packages declared in `pyproject.toml`.
+pub fn get_first_party(config: &Config) -> Result<HashSet<String>> {
+    let base_directory = &config.base_directory;
+
+    // e.g. `import utils` with `./utils.py` in a flat layout
+    let mut names = top_level_names(base_directory, true);
+    names.retain(|name| !ROOT_SCRIPTS.contains(&name.as_str()));
+    names.extend(top_level_names(&base_directory.join("src"), true));
+
+    // A malformed `pyproject.toml` is reported by the dependency readers, and the names found in
+    // the directories still apply.
+    let pyproject = base_directory.join("pyproject.toml");
+    if pyproject.is_file() {
+        match get_declared_names(&pyproject) {
+            Ok(declared) => names.extend(declared),
+            Err(e) => eprintln!("Ignoring {:?}: {:#}", pyproject, e),
+        }
+    }
+
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    use crate::project_assets::test_utils::{create_file, te_config};
+
+    #[test]
+    fn test_layouts() {
+        let temp_dir = tempdir().unwrap();
+        create_file(&temp_dir, "myapp/__init__.py", "");
+        create_file(&temp_dir, "scripts/run.py", "");
+        create_file(&temp_dir, "src/mylib/__init__.py", "");
+        create_file(&temp_dir, "src/helpers.py", "");
+        create_file(&temp_dir, "src/typed.pyi", "");
+        create_file(&temp_dir, "src/fast.pxd", "");
+        create_file(&temp_dir, "utils.py", "");
+        create_file(&temp_dir, "setup.py", "");
+        create_file(&temp_dir, "conftest.py", "");
+
+        let names = get_first_party(&te_config(temp_dir.path().to_path_buf())).unwrap();
+
+        assert_eq!(
+            names,
+            HashSet::from([
+                "myapp".to_string(),
+                "mylib".to_string(),
+                "helpers".to_string(),
+                "typed".to_string(),
+                "fast".to_string(),
+                "utils".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_declared_in_pyproject() {
+        let temp_dir = tempdir().unwrap();
+        create_file(
+            &temp_dir,
+            "pyproject.toml",
+            r#"
+[project]
+name = "My-Project"
+
+[tool.poetry]
+packages = [{ include = "poetry_pkg", from = "lib" }]
+
+[tool.setuptools]
+packages = ["setuptools_pkg", "setuptools_pkg.sub"]
+py-modules = ["cli"]
+
+[tool.hatch.build.targets.wheel]
+packages = ["src/hatch_pkg"]
+"#,
+        );
+
+        let names = get_first_party(&te_config(temp_dir.path().to_path_buf())).unwrap();
+
+        let expected = [
+            "my_project",
+            "poetry_pkg",
+            "setuptools_pkg",
+            "cli",
+            "hatch_pkg",
+        ];
+        assert_eq!(names, expected.iter().map(|s| s.to_string()).collect());
+    }
+
+    #[test]
+    fn test_malformed_pyproject() {
+        let temp_dir = tempdir().unwrap();
+        create_file(&temp_dir, "myapp/__init__.py", "");
+        create_file(&temp_dir, "pyproject.toml", "[project");
+
+        let names = get_first_party(&te_config(temp_dir.path().to_path_buf())).unwrap();
+
+        assert_eq!(names, HashSet::from(["myapp".to_string()]));
+    }
+}
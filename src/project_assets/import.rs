@@ -1,15 +1,24 @@
 use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::str;
 use std::sync::mpsc;
 use std::sync::Arc;
 
 use anyhow::Result;
-use ignore::{WalkBuilder, WalkParallel, WalkState};
+use ignore::{WalkParallel, WalkState};
+use rustpython_parser::text_size::TextRange;
 use rustpython_parser::{ast, ast::Visitor, parse, Mode};
+use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
+use crate::project_assets::cache::{content_hash, CacheEntry, FileStamp, ImportCache};
+use crate::project_assets::cython::cython_imports;
+use crate::project_assets::encoding::decode_source;
+use crate::project_assets::line_scan::scan_imports;
+use crate::project_assets::notebook::notebook_cells;
+use crate::project_assets::walk::project_walker;
 
 /// Extract the first part of an import statement
 ///  e.g. `os.path` -> `os`
@@ -17,12 +26,357 @@ use crate::config::Config;
 fn stem_import(import: &str) -> String {
     import.split('.').next().unwrap_or_default().into()
 }
+
+/// How a module is imported.
+/// Kinds are ordered from the most to the least certain.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportKind {
+    /// An `import x` or `from x import y` statement
+    Static,
+    /// A string literal passed to an import function, e.g. `importlib.import_module("x")`
+    Dynamic,
+    /// A statement found line by line in a file that does not parse, which may be wrong,
+    ///  e.g. `import x` inside a docstring
+    Recovered,
+}
+
+/// When the imported module is needed.
+/// Contexts are ordered from the most to the least needed, and the least needed one of nested
+/// blocks wins, e.g. an optional import under `if TYPE_CHECKING:` is type-only.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportContext {
+    /// Imported when the code runs.
+    #[default]
+    Runtime,
+    /// Imported in a `try:` block that handles `ImportError`, so the code runs without it.
+    Optional,
+    /// Imported in an `if TYPE_CHECKING:` block, so only type checkers need it.
+    TypeOnly,
+}
+
+/// Where a module is imported.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Location {
+    file: PathBuf,
+    /// The index of the notebook cell, for imports in a Jupyter notebook.
+    cell: Option<usize>,
+    /// The 1-based line, counted from the start of the cell in a notebook.
+    line: usize,
+    /// The 1-based column, in characters.
+    column: usize,
+}
+
+impl Location {
+    pub fn new(file: PathBuf, cell: Option<usize>, line: usize, column: usize) -> Self {
+        Self {
+            file,
+            cell,
+            line,
+            column,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    #[allow(dead_code)]
+    pub fn cell(&self) -> Option<usize> {
+        self.cell
+    }
+
+    #[allow(dead_code)]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    #[allow(dead_code)]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+/// An import of a module by the project.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Import {
+    /// The top-level module, e.g. `os`
+    name: String,
+    /// The full dotted path of what is imported, e.g. `os.path.join`
+    path: String,
+    kind: ImportKind,
+    context: ImportContext,
+    location: Location,
+}
+
+impl Import {
+    pub fn new(path: String, kind: ImportKind, location: Location) -> Self {
+        Self {
+            name: stem_import(&path),
+            path,
+            kind,
+            context: ImportContext::Runtime,
+            location,
+        }
+    }
+
+    pub fn with_context(mut self, context: ImportContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[allow(dead_code)]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn kind(&self) -> ImportKind {
+        self.kind
+    }
+
+    pub fn context(&self) -> ImportContext {
+        self.context
+    }
+
+    #[allow(dead_code)]
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+}
+
+/// Why a file could not be scanned for imports.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileErrorKind {
+    /// The file could not be read, e.g. for lack of permission.
+    Read,
+    /// The file could not be decoded, e.g. it is not valid UTF-8 and declares no other encoding.
+    Encoding,
+    /// The file is not a valid Jupyter notebook.
+    Notebook,
+    /// The file is not valid Python.
+    Parse,
+}
+
+/// A file whose imports are missing from the analysis.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FileError {
+    file: PathBuf,
+    kind: FileErrorKind,
+    message: String,
+    /// Where parsing failed, for parse errors.
+    location: Option<Box<Location>>,
+    /// The imports still found in a file that does not parse, with less confidence.
+    #[serde(skip)]
+    recovered: Vec<Import>,
+}
+
+impl FileError {
+    fn new(file: &Path, kind: FileErrorKind, message: String, location: Option<Location>) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            kind,
+            message,
+            location: location.map(Box::new),
+            recovered: Vec::new(),
+        }
+    }
+
+    fn with_recovered(mut self, recovered: Vec<Import>) -> Self {
+        self.recovered = recovered;
+        self
+    }
+
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    pub fn kind(&self) -> FileErrorKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn location(&self) -> Option<&Location> {
+        self.location.as_deref()
+    }
+
+    pub fn recovered(&self) -> &[Import] {
+        &self.recovered
+    }
+}
+
+/// The imports of a file, with the entry to cache them in and the errors of the notebook cells that
+/// do not parse, or why the file could not be scanned.
+type FileResult = Result<(PathBuf, CacheEntry, Vec<FileError>), FileError>;
+
+/// Functions that import the module named by their first argument.
+/// Bare names cover `from importlib import import_module` and the like.
+const DYNAMIC_IMPORT_FUNCTIONS: [&str; 11] = [
+    "__import__",
+    "importlib.__import__",
+    "importlib.import_module",
+    "import_module",
+    "importlib.util.find_spec",
+    "util.find_spec",
+    "find_spec",
+    "pkgutil.find_loader",
+    "pkgutil.get_loader",
+    "pkgutil.resolve_name",
+    "resolve_name",
+];
+
+/// The dotted name of a called function, e.g. `importlib.util.find_spec`
+fn dotted_name(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Name(name) => Some(name.id.to_string()),
+        ast::Expr::Attribute(attribute) => {
+            dotted_name(&attribute.value).map(|value| format!("{}.{}", value, attribute.attr))
+        }
+        _ => None,
+    }
+}
+
+/// Whether the condition of an `if` is `TYPE_CHECKING`, e.g. `typing.TYPE_CHECKING`
+fn is_type_checking(test: &ast::Expr) -> bool {
+    dotted_name(test).is_some_and(|name| name.rsplit('.').next() == Some("TYPE_CHECKING"))
+}
+
+/// Whether an `except` clause handles a failed import,
+///  e.g. `except ImportError:` or `except (ModuleNotFoundError, AttributeError):`
+fn handles_import_error(handler: &ast::ExceptHandler) -> bool {
+    let ast::ExceptHandler::ExceptHandler(handler) = handler;
+    let is_import_error = |expr: &ast::Expr| {
+        dotted_name(expr).is_some_and(|name| {
+            let name = name.rsplit('.').next().unwrap_or_default();
+            name == "ImportError" || name == "ModuleNotFoundError"
+        })
+    };
+    match handler.type_.as_deref() {
+        Some(ast::Expr::Tuple(tuple)) => tuple.elts.iter().any(is_import_error),
+        Some(expr) => is_import_error(expr),
+        None => false,
+    }
+}
+
+/// Maps the byte offsets of the parsed source back to locations in the file.
+struct SourceMap<'a> {
+    file: &'a Path,
+    source: &'a str,
+    /// The byte offset each line of `source` starts at.
+    line_starts: Vec<usize>,
+    /// The index of the notebook cell the source comes from, if the file is a notebook.
+    cell: Option<usize>,
+}
+
+impl<'a> SourceMap<'a> {
+    fn new(file: &'a Path, source: &'a str, cell: Option<usize>) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self {
+            file,
+            source,
+            line_starts,
+            cell,
+        }
+    }
+
+    /// The 0-based index of the line holding the given byte offset of the source.
+    fn line_index(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|start| *start <= offset) - 1
+    }
+
+    /// Converts a line and a column in bytes, both 0-based, of the source to a location in the
+    /// file.
+    fn locate_line(&self, line: usize, column: usize) -> Location {
+        self.locate(self.line_starts[line] + column)
+    }
+
+    /// Converts a byte offset of the source to a location in the file.
+    fn locate(&self, offset: usize) -> Location {
+        let line = self.line_index(offset);
+        let column = self
+            .source
+            .get(self.line_starts[line]..offset)
+            .map_or(0, |text| text.chars().count())
+            + 1;
+        Location::new(self.file.to_path_buf(), self.cell, line + 1, column)
+    }
+}
+
 /// Collects all the dependencies from the AST
-struct ImportCollector {
-    imports: HashSet<String>,
+struct ImportCollector<'a> {
+    imports: HashSet<Import>,
+    source_map: SourceMap<'a>,
+    /// The context of the block being visited.
+    context: ImportContext,
+}
+
+impl<'a> ImportCollector<'a> {
+    fn new(source_map: SourceMap<'a>, context: ImportContext) -> Self {
+        Self {
+            imports: HashSet::new(),
+            source_map,
+            context,
+        }
+    }
+
+    fn insert(&mut self, path: String, kind: ImportKind, range: TextRange) {
+        let location = self.source_map.locate(range.start().to_usize());
+        self.imports
+            .insert(Import::new(path, kind, location).with_context(self.context));
+    }
+
+    /// Visits a block of statements in the given context, unless it is already in a less
+    /// needed one.
+    fn visit_block(&mut self, context: ImportContext, body: Vec<ast::Stmt>) {
+        let outer = self.context;
+        self.context = outer.max(context);
+        body.into_iter().for_each(|stmt| self.visit_stmt(stmt));
+        self.context = outer;
+    }
+
+    /// Visits a `try:` or `try/except*` statement: the imports of a `try:` block that handles
+    /// `ImportError`, and the fallback imports of its handlers, are optional.
+    fn visit_try(
+        &mut self,
+        body: Vec<ast::Stmt>,
+        handlers: Vec<ast::ExceptHandler>,
+        orelse: Vec<ast::Stmt>,
+        finalbody: Vec<ast::Stmt>,
+    ) {
+        let context = if handlers.iter().any(handles_import_error) {
+            ImportContext::Optional
+        } else {
+            ImportContext::Runtime
+        };
+        self.visit_block(context, body);
+        for handler in handlers {
+            let handler_context = if handles_import_error(&handler) {
+                ImportContext::Optional
+            } else {
+                ImportContext::Runtime
+            };
+            let ast::ExceptHandler::ExceptHandler(handler) = handler;
+            self.visit_block(handler_context, handler.body);
+        }
+        self.visit_block(ImportContext::Runtime, orelse);
+        self.visit_block(ImportContext::Runtime, finalbody);
+    }
 }
 
-impl Visitor for ImportCollector {
+impl Visitor for ImportCollector<'_> {
     /// This is a generic visit method that will be called for all nodes
     fn visit_stmt(&mut self, node: ast::Stmt<ast::text_size::TextRange>) {
         self.generic_visit_stmt(node);
@@ -30,72 +384,264 @@ impl Visitor for ImportCollector {
     /// This method is `overridden` to collect the dependencies into `self.deps`
     fn visit_stmt_import(&mut self, node: ast::StmtImport) {
         node.names.iter().for_each(|alias| {
-            self.imports.insert(stem_import(&alias.name));
+            self.insert(alias.name.to_string(), ImportKind::Static, alias.range);
         })
     }
 
     /// This method is `overridden` to collect the dependencies into `self.deps`
+    /// Relative imports (e.g. `from .utils import x`) always refer to the project itself.
     fn visit_stmt_import_from(&mut self, node: ast::StmtImportFrom) {
+        if node.level.is_some_and(|level| level.to_u32() > 0) {
+            return;
+        }
         if let Some(module) = &node.module {
-            self.imports.insert(stem_import(module));
+            // Each imported name is a usage, e.g. `from os import path` -> `os.path`
+            node.names.iter().for_each(|alias| {
+                let path = match alias.name.as_str() {
+                    "*" => module.to_string(),
+                    name => format!("{}.{}", module, name),
+                };
+                self.insert(path, ImportKind::Static, alias.range);
+            })
+        }
+    }
+
+    /// This method is `overridden` to tag the imports under `if TYPE_CHECKING:` as type-only.
+    fn visit_stmt_if(&mut self, node: ast::StmtIf) {
+        if !is_type_checking(&node.test) {
+            return self.generic_visit_stmt_if(node);
+        }
+        self.visit_block(ImportContext::TypeOnly, node.body);
+        self.visit_block(ImportContext::Runtime, node.orelse);
+    }
+
+    /// This method is `overridden` to tag the imports of a `try:` block that handles
+    /// `ImportError`, and the fallback imports of its handlers, as optional.
+    fn visit_stmt_try(&mut self, node: ast::StmtTry) {
+        self.visit_try(node.body, node.handlers, node.orelse, node.finalbody);
+    }
+
+    /// This method is `overridden` to treat `except* ImportError:` like `except ImportError:`.
+    fn visit_stmt_try_star(&mut self, node: ast::StmtTryStar) {
+        self.visit_try(node.body, node.handlers, node.orelse, node.finalbody);
+    }
+
+    /// This method is `overridden` to collect modules imported by name at runtime,
+    ///  e.g. `importlib.import_module("redis")`, `__import__("ujson")`
+    fn visit_expr_call(&mut self, node: ast::ExprCall) {
+        let is_import_function = dotted_name(&node.func)
+            .is_some_and(|name| DYNAMIC_IMPORT_FUNCTIONS.contains(&name.as_str()));
+
+        if is_import_function {
+            let argument = node.args.first().or_else(|| {
+                node.keywords
+                    .iter()
+                    .find(|keyword| {
+                        keyword
+                            .arg
+                            .as_ref()
+                            .is_some_and(|arg| arg.as_str() == "name")
+                    })
+                    .map(|keyword| &keyword.value)
+            });
+            if let Some(ast::Expr::Constant(ast::ExprConstant {
+                value: ast::Constant::Str(module),
+                ..
+            })) = argument
+            {
+                // Relative names (e.g. `import_module(".plugins", __package__)`) are the project's own,
+                // and `pkgutil.resolve_name` takes `module:attribute` names.
+                if !module.starts_with('.') {
+                    let module = module.split(':').next().unwrap_or_default();
+                    self.insert(module.to_string(), ImportKind::Dynamic, node.range);
+                }
+            }
         }
+
+        self.generic_visit_expr_call(node);
+    }
+}
+
+/// The extensions of the files imports are collected from: Python sources and stubs, notebooks
+/// and Cython sources.
+const SOURCE_EXTENSIONS: [&str; 6] = ["py", "pyi", "ipynb", "pyx", "pxd", "pxi"];
+
+/// Cython sources, which are not valid Python.
+const CYTHON_EXTENSIONS: [&str; 3] = ["pyx", "pxd", "pxi"];
+
+/// Whether the file is a Python script without an extension, e.g. `#!/usr/bin/env python3`
+fn has_python_shebang(path: &Path) -> bool {
+    let mut head = Vec::new();
+    let Ok(_) = fs::File::open(path).and_then(|file| file.take(128).read_to_end(&mut head)) else {
+        return false;
+    };
+    let first_line = head.split(|byte| *byte == b'\n').next().unwrap_or_default();
+    first_line.starts_with(b"#!") && String::from_utf8_lossy(first_line).contains("python")
+}
+
+/// Whether imports are collected from the file.
+fn is_source_file(path: &Path) -> bool {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => SOURCE_EXTENSIONS.contains(&ext),
+        None => has_python_shebang(path),
     }
 }
 
 fn build_walker(config: &Config) -> Result<WalkParallel> {
-    let builder = WalkBuilder::new(&config.base_directory)
-        .hidden(config.ignore_hidden)
-        .max_depth(config.max_depth)
+    let builder = project_walker(config, &config.include)?
         .filter_entry(|entry| {
-            entry.file_type().map_or(false, |ft| ft.is_dir())
-                || entry.path().extension().map_or(false, |ext| ext == "py")
+            entry.file_type().is_some_and(|ft| ft.is_dir()) || is_source_file(entry.path())
         })
         .build_parallel();
 
     Ok(builder)
 }
 
-/// Spawns a thread to process a Python file and extract import statements.
-fn sender(path: PathBuf, tx: Arc<mpsc::Sender<HashSet<String>>>) {
-    let content = match fs::read_to_string(&path) {
-        Ok(content) => content,
-        Err(e) => {
-            eprintln!("Error reading file {:?}: {}", path, e);
-            return;
-        }
-    };
+/// Parses a Python file, a notebook or a Cython source, and extracts its import statements.
+/// The imports of `.pyi` stubs are type-only, as only type checkers read stubs.
+/// Each cell of a notebook is parsed on its own, so a cell that does not parse only loses its own
+/// imports, and is returned as an error alongside those of the other cells.
+fn parse_imports(
+    path: &Path,
+    content: &str,
+) -> Result<(HashSet<Import>, Vec<FileError>), FileError> {
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    if extension.is_some_and(|ext| CYTHON_EXTENSIONS.contains(&ext)) {
+        return Ok((cython_imports(path, content), Vec::new()));
+    }
 
-    let ast = match parse(&content, Mode::Module, "<embedded>") {
-        Ok(ast) => ast,
-        Err(e) => {
-            eprintln!("Error parsing file {:?}: {}", path, e);
-            return;
-        }
+    // Notebooks are parsed as the Python source of their code cells.
+    let cells = if extension == Some("ipynb") {
+        let cells = notebook_cells(content)
+            .map_err(|e| FileError::new(path, FileErrorKind::Notebook, format!("{:#}", e), None))?;
+        Some(cells)
+    } else {
+        None
+    };
+    let sources: Vec<(Option<usize>, &str)> = match &cells {
+        Some(cells) => cells
+            .iter()
+            .map(|(cell, source)| (Some(*cell), source.as_str()))
+            .collect(),
+        None => vec![(None, content)],
     };
 
-    let mut collector = ImportCollector {
-        imports: HashSet::new(),
+    let context = if extension == Some("pyi") {
+        ImportContext::TypeOnly
+    } else {
+        ImportContext::Runtime
     };
+    let mut imports = HashSet::new();
+    let mut errors = Vec::new();
+    for (cell, source) in sources {
+        match parse_source(SourceMap::new(path, source, cell), context) {
+            Ok(source_imports) => imports.extend(source_imports),
+            Err(error) => errors.push(error),
+        }
+    }
+    Ok((imports, errors))
+}
+
+/// Parses a Python source and extracts its import statements, in the given context at most.
+fn parse_source(
+    source_map: SourceMap,
+    context: ImportContext,
+) -> Result<HashSet<Import>, FileError> {
+    let ast = parse(source_map.source, Mode::Module, "<embedded>").map_err(|e| {
+        let location = source_map.locate(e.offset.to_usize());
+        FileError::new(
+            source_map.file,
+            FileErrorKind::Parse,
+            e.error.to_string(),
+            Some(location),
+        )
+        .with_recovered(recover_imports(&source_map, context))
+    })?;
+
+    let mut collector = ImportCollector::new(source_map, context);
     ast.module()
         .unwrap()
         .body
         .into_iter()
         .for_each(|node| collector.visit_stmt(node));
+    Ok(collector.imports)
+}
+
+/// Scans a source that does not parse line by line, so a single syntax error does not lose all of
+/// its imports. The imports found this way are `Recovered` ones.
+fn recover_imports(source_map: &SourceMap, context: ImportContext) -> Vec<Import> {
+    scan_imports(source_map.source)
+        .into_iter()
+        .map(|import| {
+            let location = source_map.locate_line(import.line, import.column - 1);
+            Import::new(import.path, ImportKind::Recovered, location).with_context(context)
+        })
+        .collect()
+}
+
+/// Reads and parses a Python file, unless it has not changed since the cache was written.
+/// Returns the errors of the notebook cells that do not parse alongside.
+fn process_file(
+    path: &Path,
+    cache: &ImportCache,
+) -> Result<(CacheEntry, Vec<FileError>), FileError> {
+    let read_error =
+        |e: std::io::Error| FileError::new(path, FileErrorKind::Read, e.to_string(), None);
+
+    let stamp = FileStamp::of(path).map_err(read_error)?;
+    if let Some(entry) = cache.get_unchanged(path, stamp) {
+        return Ok((entry.clone(), Vec::new()));
+    }
+
+    let bytes = fs::read(path).map_err(read_error)?;
+    let content = decode_source(&bytes)
+        .map_err(|e| FileError::new(path, FileErrorKind::Encoding, format!("{:#}", e), None))?;
+    let hash = content_hash(&content);
+    if let Some(entry) = cache.get_same_content(path, hash) {
+        let entry = CacheEntry {
+            stamp,
+            ..entry.clone()
+        };
+        return Ok((entry, Vec::new()));
+    }
+
+    let (imports, errors) = parse_imports(path, &content)?;
+    let entry = CacheEntry {
+        stamp,
+        hash,
+        imports: imports.into_iter().collect(),
+    };
+    Ok((entry, errors))
+}
+
+/// Spawns a thread to process a Python file and extract import statements.
+fn sender(path: PathBuf, cache: &ImportCache, tx: Arc<mpsc::Sender<FileResult>>) {
+    let result = process_file(&path, cache).map(|(entry, errors)| (path.clone(), entry, errors));
 
     // Attempt to send collected imports, log any failure to do so.
-    if tx.send(collector.imports).is_err() {
+    if tx.send(result).is_err() {
         eprintln!("Failed to send data for file {:?}", path);
     }
 }
 
 /// Initiates the parallel processing of Python files to extract import statements.
-pub fn get_imports(config: &Config) -> Result<HashSet<String>> {
+/// Files that cannot be read or parsed are returned alongside, as their imports are missing, or
+/// only recovered line by line.
+/// With `config.cache`, the imports of each file are kept in `.unpack_cache/` for the next run.
+pub fn get_imports(config: &Config) -> Result<(HashSet<Import>, Vec<FileError>)> {
     let walker = build_walker(config)?;
     let (tx, rx) = mpsc::channel();
     let tx = Arc::new(tx);
+    let cache = Arc::new(if config.cache {
+        ImportCache::load(&config.base_directory)
+    } else {
+        ImportCache::new()
+    });
 
+    let previous_cache = Arc::clone(&cache);
     walker.run(move || {
         let tx = Arc::clone(&tx);
+        let cache = Arc::clone(&cache);
         Box::new(move |result| {
             if let Ok(entry) = result {
                 if entry.depth() == 0 {
@@ -108,19 +654,48 @@ pub fn get_imports(config: &Config) -> Result<HashSet<String>> {
                 }
 
                 let tx = tx.clone();
-                sender(path, tx);
+                sender(path, &cache, tx);
             }
             WalkState::Continue
         })
     });
 
-    // Collect all the import statements from the threads
+    // Collect all the import statements from the threads.
+    // The new cache only holds the files that still exist, and those scanned this run only if
+    // they have no errors, so these are reported again.
     let mut imports = HashSet::new();
-    for recieved in rx.iter() {
-        imports.extend(recieved);
+    let mut errors = Vec::new();
+    let mut cache = previous_cache.existing();
+    for result in rx.iter() {
+        let file_errors = match result {
+            Ok((path, entry, file_errors)) => {
+                imports.extend(entry.imports.iter().cloned());
+                if file_errors.is_empty() {
+                    cache.insert(path, entry);
+                } else {
+                    cache.remove(&path);
+                }
+                file_errors
+            }
+            Err(error) => {
+                cache.remove(error.file());
+                vec![error]
+            }
+        };
+        for error in file_errors {
+            imports.extend(error.recovered().iter().cloned());
+            errors.push(error);
+        }
+    }
+    errors.sort_by(|a: &FileError, b| a.file.cmp(&b.file));
+
+    if config.cache {
+        if let Err(e) = cache.save(&config.base_directory) {
+            eprintln!("Failed to save the import cache: {:#}", e);
+        }
     }
 
-    Ok(imports)
+    Ok((imports, errors))
 }
 
 #[cfg(test)]
@@ -130,29 +705,11 @@ mod tests {
     use std::io::Write;
     use tempfile::tempdir;
 
-    use crate::cli::{DepType, Env, OutputKind};
-    use crate::project_assets::PackageState;
-
-    /// Helper function to create a Python file in the temporary directory.
-    fn create_file(dir: &tempfile::TempDir, filename: &str, content: &str) -> PathBuf {
-        let file_path = dir.path().join(filename);
-        let mut file = File::create(&file_path).expect("Failed to create file.");
-        writeln!(file, "{}", content).expect("Failed to write to file.");
-        file_path
-    }
-
-    /// Helper function to create a Config struct for testing.
-    fn te_config(base_directory: PathBuf) -> Config {
-        Config {
-            base_directory,
-            ignore_hidden: true,
-            max_depth: None,
-            package_state: PackageState::Unused,
-            dep_spec_file: PathBuf::new(),
-            env: Env::Test,
-            output: OutputKind::Human,
-            dep_type: DepType::Poetry,
-        }
+    use crate::project_assets::test_utils::{create_file, te_config};
+
+    /// Helper function to get the names of the imports.
+    fn names(imports: &HashSet<Import>) -> HashSet<&str> {
+        imports.iter().map(Import::name).collect()
     }
 
     /// Tests the `stem_import` function for correct behavior.
@@ -177,7 +734,8 @@ mod tests {
 
         let config = te_config(temp_dir.path().to_path_buf());
 
-        let imports = get_imports(&config).expect("Failed to get imports");
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+        let imports = names(&imports);
 
         assert!(imports.contains("os"));
         assert!(imports.contains("sys"));
@@ -196,7 +754,10 @@ mod tests {
         let imports = get_imports(&config);
 
         assert!(imports.is_ok());
-        assert!(imports.unwrap().is_empty());
+        let (imports, errors) = imports.unwrap();
+        assert!(imports.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), FileErrorKind::Parse);
     }
 
     /// Tests handling of a directory with multiple Python files.
@@ -217,7 +778,8 @@ mod tests {
 
         let config = te_config(temp_dir.path().to_path_buf());
 
-        let imports = get_imports(&config).expect("Failed to get imports");
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+        let imports = names(&imports);
 
         assert!(imports.contains("os"));
         assert!(imports.contains("sys"));
@@ -227,6 +789,23 @@ mod tests {
         assert!(imports.contains("matplotlib"));
     }
 
+    #[test]
+    fn test_skip_relative_imports() {
+        let temp_dir = tempdir().unwrap();
+        create_file(
+            &temp_dir,
+            "module.py",
+            "from .utils import helper\nfrom .. import sibling\nfrom requests import get",
+        );
+
+        let config = te_config(temp_dir.path().to_path_buf());
+
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+        let imports = names(&imports);
+
+        assert_eq!(imports, HashSet::from(["requests"]));
+    }
+
     #[test]
     fn test_ignore_non_python_files() {
         let temp_dir = tempdir().unwrap();
@@ -239,7 +818,8 @@ mod tests {
 
         let config = te_config(temp_dir.path().to_path_buf());
 
-        let imports = get_imports(&config).expect("Failed to get imports");
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+        let imports = names(&imports);
 
         assert!(
             imports.contains("os"),
@@ -251,4 +831,442 @@ mod tests {
             "Only Python file imports should be processed."
         );
     }
+
+    #[test]
+    fn test_dynamic_imports() {
+        let temp_dir = tempdir().unwrap();
+        create_file(
+            &temp_dir,
+            "plugins.py",
+            r#"
+import importlib
+from importlib import import_module
+
+backend = importlib.import_module("redis.asyncio")
+json = __import__("ujson")
+spec = importlib.util.find_spec(name="orjson")
+plugin = import_module(".local_plugin", __package__)
+entry = pkgutil.resolve_name("celery.app:Celery")
+dynamic = importlib.import_module(module_name)
+"#,
+        );
+
+        let config = te_config(temp_dir.path().to_path_buf());
+
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+
+        let mut dynamic: Vec<&str> = imports
+            .iter()
+            .filter(|import| import.kind() == ImportKind::Dynamic)
+            .map(Import::name)
+            .collect();
+        dynamic.sort();
+        assert_eq!(dynamic, vec!["celery", "orjson", "redis", "ujson"]);
+        let location = Location::new(temp_dir.path().join("plugins.py"), None, 2, 8);
+        assert!(imports.contains(&Import::new(
+            "importlib".to_string(),
+            ImportKind::Static,
+            location
+        )));
+        let location = Location::new(temp_dir.path().join("plugins.py"), None, 5, 11);
+        assert!(imports.contains(&Import::new(
+            "redis.asyncio".to_string(),
+            ImportKind::Dynamic,
+            location
+        )));
+    }
+
+    #[test]
+    fn test_notebook_imports() {
+        let temp_dir = tempdir().unwrap();
+        create_file(
+            &temp_dir,
+            "analysis.ipynb",
+            r#"{
+                "cells": [
+                    { "cell_type": "markdown", "source": ["import not_code"] },
+                    { "cell_type": "code", "source": ["%matplotlib inline\n", "import pandas as pd"] },
+                    { "cell_type": "code", "source": ["!pip install seaborn\n", "from sklearn import svm"] }
+                ],
+                "metadata": {},
+                "nbformat": 4,
+                "nbformat_minor": 5
+            }"#,
+        );
+
+        let config = te_config(temp_dir.path().to_path_buf());
+
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+
+        let mut cells: Vec<(&str, Option<usize>, usize)> = imports
+            .iter()
+            .map(|import| {
+                let location = import.location();
+                (import.name(), location.cell(), location.line())
+            })
+            .collect();
+        cells.sort();
+        assert_eq!(cells, vec![("pandas", Some(1), 2), ("sklearn", Some(2), 2)]);
+        assert!(imports
+            .iter()
+            .all(|import| import.location().file() == temp_dir.path().join("analysis.ipynb")));
+    }
+
+    /// A cell that does not parse only loses its own imports.
+    #[test]
+    fn test_notebook_cell_errors() {
+        let temp_dir = tempdir().unwrap();
+        create_file(
+            &temp_dir,
+            "analysis.ipynb",
+            r#"{
+                "cells": [
+                    { "cell_type": "code", "source": ["import pandas as pd"] },
+                    { "cell_type": "code", "source": ["import numpy\n", "print \"python 2\""] },
+                    { "cell_type": "code", "source": ["from sklearn import svm"] },
+                    { "cell_type": "code", "source": ["def f(:\n", "    pass"] }
+                ],
+                "metadata": {},
+                "nbformat": 4,
+                "nbformat_minor": 5
+            }"#,
+        );
+
+        let config = te_config(temp_dir.path().to_path_buf());
+
+        let (imports, errors) = get_imports(&config).expect("Failed to get imports");
+
+        let mut kinds: Vec<(&str, ImportKind)> = imports
+            .iter()
+            .map(|import| (import.name(), import.kind()))
+            .collect();
+        kinds.sort();
+        assert_eq!(
+            kinds,
+            vec![
+                ("numpy", ImportKind::Recovered),
+                ("pandas", ImportKind::Static),
+                ("sklearn", ImportKind::Static),
+            ]
+        );
+
+        // Each cell that does not parse is its own error, located in the cell.
+        let locations: Vec<(FileErrorKind, Option<usize>, usize)> = errors
+            .iter()
+            .map(|error| {
+                let location = error.location().expect("Parse errors have a location");
+                (error.kind(), location.cell(), location.line())
+            })
+            .collect();
+        assert_eq!(
+            locations,
+            vec![
+                (FileErrorKind::Parse, Some(1), 2),
+                (FileErrorKind::Parse, Some(3), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_locations() {
+        let temp_dir = tempdir().unwrap();
+        create_file(
+            &temp_dir,
+            "app.py",
+            "import os.path, requests\nfrom django.db import models, connection\n\ndef main():\n    from yaml import *",
+        );
+
+        let config = te_config(temp_dir.path().to_path_buf());
+
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+
+        let mut usages: Vec<(&str, &str, usize, usize)> = imports
+            .iter()
+            .map(|import| {
+                let location = import.location();
+                (
+                    import.name(),
+                    import.path(),
+                    location.line(),
+                    location.column(),
+                )
+            })
+            .collect();
+        usages.sort();
+        assert_eq!(
+            usages,
+            vec![
+                ("django", "django.db.connection", 2, 31),
+                ("django", "django.db.models", 2, 23),
+                ("os", "os.path", 1, 8),
+                ("requests", "requests", 1, 17),
+                ("yaml", "yaml", 5, 22),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_contexts() {
+        let temp_dir = tempdir().unwrap();
+        create_file(
+            &temp_dir,
+            "app.py",
+            r#"
+from typing import TYPE_CHECKING
+import requests
+
+if TYPE_CHECKING:
+    from pandas import DataFrame
+    try:
+        import numpy
+    except ImportError:
+        pass
+else:
+    import attrs
+
+try:
+    import ujson as json
+except (ImportError, AttributeError):
+    import simplejson as json
+
+try:
+    import redis
+except ValueError:
+    import celery
+
+try:
+    import orjson
+except* ImportError:
+    pass
+
+def load():
+    if typing.TYPE_CHECKING:
+        import yaml
+"#,
+        );
+
+        let config = te_config(temp_dir.path().to_path_buf());
+
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+
+        let mut contexts: Vec<(&str, ImportContext)> = imports
+            .iter()
+            .map(|import| (import.name(), import.context()))
+            .collect();
+        contexts.sort();
+        assert_eq!(
+            contexts,
+            vec![
+                ("attrs", ImportContext::Runtime),
+                ("celery", ImportContext::Runtime),
+                ("numpy", ImportContext::TypeOnly),
+                ("orjson", ImportContext::Optional),
+                ("pandas", ImportContext::TypeOnly),
+                ("redis", ImportContext::Runtime),
+                ("requests", ImportContext::Runtime),
+                ("simplejson", ImportContext::Optional),
+                ("typing", ImportContext::Runtime),
+                ("ujson", ImportContext::Optional),
+                ("yaml", ImportContext::TypeOnly),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cached_imports() {
+        let temp_dir = tempdir().unwrap();
+        create_file(&temp_dir, "app.py", "import requests");
+        create_file(&temp_dir, "cli.py", "import click");
+
+        let mut config = te_config(temp_dir.path().to_path_buf());
+        config.cache = true;
+
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+        assert_eq!(names(&imports), HashSet::from(["requests", "click"]));
+        assert!(temp_dir.path().join(".unpack_cache/imports.json").is_file());
+
+        // Changed files are parsed again, and removed ones are forgotten.
+        create_file(&temp_dir, "app.py", "import requests\nimport yaml");
+        fs::remove_file(temp_dir.path().join("cli.py")).unwrap();
+
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+        assert_eq!(names(&imports), HashSet::from(["requests", "yaml"]));
+        let cache = ImportCache::load(temp_dir.path());
+        let cli = temp_dir.path().join("cli.py");
+        assert!(cache
+            .get_same_content(&cli, content_hash("import click\n"))
+            .is_none());
+
+        // Files left out of a run stay cached for the next one.
+        config.include = vec!["cli.py".to_string()];
+        create_file(&temp_dir, "cli.py", "import click");
+        get_imports(&config).expect("Failed to get imports");
+        let cache = ImportCache::load(temp_dir.path());
+        let app = temp_dir.path().join("app.py");
+        assert!(cache
+            .get_same_content(&app, content_hash("import requests\nimport yaml\n"))
+            .is_some());
+    }
+
+    #[test]
+    fn test_file_errors() {
+        let temp_dir = tempdir().unwrap();
+        create_file(&temp_dir, "app.py", "import requests");
+        create_file(&temp_dir, "broken.py", "import os\n\ndef main(:\n    pass");
+        create_file(&temp_dir, "notebook.ipynb", "not json");
+        fs::write(
+            temp_dir.path().join("latin1.py"),
+            b"# caf\xe9\nimport yaml\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("declared.py"),
+            b"# -*- coding: latin-1 -*-\n# caf\xe9\nimport toml\n",
+        )
+        .unwrap();
+
+        let config = te_config(temp_dir.path().to_path_buf());
+
+        let (imports, errors) = get_imports(&config).expect("Failed to get imports");
+
+        // The imports of a file that does not parse are still recovered line by line.
+        assert_eq!(names(&imports), HashSet::from(["os", "requests", "toml"]));
+        let kinds: Vec<(&Path, FileErrorKind)> = errors
+            .iter()
+            .map(|error| (error.file(), error.kind()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    temp_dir.path().join("broken.py").as_path(),
+                    FileErrorKind::Parse
+                ),
+                (
+                    temp_dir.path().join("latin1.py").as_path(),
+                    FileErrorKind::Encoding
+                ),
+                (
+                    temp_dir.path().join("notebook.ipynb").as_path(),
+                    FileErrorKind::Notebook
+                ),
+            ]
+        );
+        let location = errors[0].location().expect("Parse errors have a location");
+        assert_eq!((location.line(), location.column()), (3, 10));
+        assert!(errors[1].location().is_none());
+    }
+
+    #[test]
+    fn test_source_files() {
+        let temp_dir = tempdir().unwrap();
+        create_file(&temp_dir, "stubs.pyi", "import numpy");
+        create_file(&temp_dir, "fast.pyx", "cimport scipy\ncdef int x = 1");
+        create_file(&temp_dir, "fast.pxd", "from sklearn.tree cimport _tree");
+        create_file(&temp_dir, "manage", "#!/usr/bin/env python3\nimport django");
+        create_file(&temp_dir, "deploy", "#!/bin/sh\nimport boto3");
+        create_file(&temp_dir, "README", "import flask");
+
+        let config = te_config(temp_dir.path().to_path_buf());
+
+        let (imports, errors) = get_imports(&config).expect("Failed to get imports");
+        assert!(errors.is_empty());
+        assert_eq!(
+            names(&imports),
+            HashSet::from(["numpy", "scipy", "sklearn", "django"])
+        );
+
+        // Only type checkers read stubs.
+        let numpy = imports.iter().find(|import| import.name() == "numpy");
+        assert_eq!(numpy.map(Import::context), Some(ImportContext::TypeOnly));
+        let django = imports.iter().find(|import| import.name() == "django");
+        assert_eq!(django.map(Import::context), Some(ImportContext::Runtime));
+    }
+
+    #[test]
+    fn test_include_and_exclude() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("src/migrations")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("vendor")).unwrap();
+        create_file(&temp_dir, "src/app.py", "import flask");
+        create_file(&temp_dir, "src/migrations/0001.py", "import alembic");
+        create_file(&temp_dir, "vendor/six.py", "import six");
+        create_file(&temp_dir, "setup.py", "import setuptools");
+        create_file(&temp_dir, ".ignore", "vendor/");
+
+        let mut config = te_config(temp_dir.path().to_path_buf());
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+        assert_eq!(
+            names(&imports),
+            HashSet::from(["flask", "alembic", "setuptools"])
+        );
+
+        config.exclude = vec!["migrations/".to_string()];
+        config.include = vec!["src/**".to_string()];
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+        assert_eq!(names(&imports), HashSet::from(["flask"]));
+
+        // Without `.ignore` files, only the globs decide.
+        config.include = Vec::new();
+        config.ignore_files = false;
+        let (imports, _) = get_imports(&config).expect("Failed to get imports");
+        assert_eq!(
+            names(&imports),
+            HashSet::from(["flask", "six", "setuptools"])
+        );
+    }
+
+    #[test]
+    fn test_recovered_imports() {
+        let temp_dir = tempdir().unwrap();
+        create_file(
+            &temp_dir,
+            "legacy.py",
+            "import os\nprint \"starting\"\n\ndef main():\n    from requests import get",
+        );
+        create_file(
+            &temp_dir,
+            "analysis.ipynb",
+            r#"{
+                "cells": [
+                    { "cell_type": "code", "source": ["import numpy as np"] },
+                    { "cell_type": "code", "source": ["x = (\n", "import pandas"] }
+                ]
+            }"#,
+        );
+
+        let config = te_config(temp_dir.path().to_path_buf());
+
+        let (imports, errors) = get_imports(&config).expect("Failed to get imports");
+        assert_eq!(errors.len(), 2);
+        // Only the notebook cell that does not parse is scanned line by line.
+        assert_eq!(errors[0].recovered().len(), 1);
+        assert_eq!(errors[1].recovered().len(), 2);
+        assert!(imports
+            .iter()
+            .all(|import| (import.kind() == ImportKind::Recovered) != (import.name() == "numpy")));
+
+        let mut locations: Vec<(&str, Option<usize>, usize, usize)> = imports
+            .iter()
+            .map(|import| {
+                let location = import.location();
+                (
+                    import.path(),
+                    location.cell(),
+                    location.line(),
+                    location.column(),
+                )
+            })
+            .collect();
+        locations.sort();
+        assert_eq!(
+            locations,
+            vec![
+                ("numpy", Some(0), 1, 8),
+                ("os", None, 1, 1),
+                ("pandas", Some(1), 2, 1),
+                ("requests.get", None, 5, 5),
+            ]
+        );
+    }
 }
@@ -0,0 +1,140 @@
+/// An import statement found by reading a source line by line, rather than parsing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedImport {
+    /// The full dotted path of what is imported, e.g. `os.path.join`
+    pub path: String,
+    /// The 0-based index of the line the statement starts on.
+    pub line: usize,
+    /// The 1-based column the statement starts at, in bytes.
+    pub column: usize,
+}
+
+/// The code of a line, without its comment.
+fn strip_comment(line: &str) -> &str {
+    line.split('#').next().unwrap_or_default()
+}
+
+/// Whether a statement goes on to the next line, after a backslash or an unclosed parenthesis.
+fn is_continued(statement: &str) -> bool {
+    statement.ends_with('\\') || statement.matches('(').count() > statement.matches(')').count()
+}
+
+/// The imported names of a statement, e.g. `numpy as np, scipy` -> `numpy`, `scipy`
+fn imported_names(names: &str) -> impl Iterator<Item = &str> {
+    names
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split(',')
+        .filter_map(|name| name.split_whitespace().next())
+}
+
+/// The dotted paths imported by a statement.
+///  e.g. `cimport numpy as cnp` -> `numpy`, `from libc.math cimport sqrt` -> `libc.math.sqrt`
+fn statement_paths(statement: &str) -> Vec<String> {
+    let mut words = statement.splitn(2, char::is_whitespace);
+    match (words.next(), words.next()) {
+        (Some("import" | "cimport"), Some(names)) => {
+            imported_names(names).map(str::to_string).collect()
+        }
+        (Some("from"), Some(rest)) => {
+            let mut words = rest.trim_start().splitn(3, char::is_whitespace);
+            let (Some(module), Some("import" | "cimport"), Some(names)) =
+                (words.next(), words.next(), words.next())
+            else {
+                return Vec::new();
+            };
+            // Relative imports always refer to the project itself.
+            if module.starts_with('.') {
+                return Vec::new();
+            }
+            imported_names(names)
+                .map(|name| match name {
+                    "*" => module.to_string(),
+                    name => format!("{}.{}", module, name),
+                })
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Finds the `import x`, `from x import y` and Cython `cimport` statements of a source without
+/// parsing it, so it works on sources the Python parser rejects.
+/// Statements are read line by line, following parentheses and backslash continuations.
+pub fn scan_imports(source: &str) -> Vec<ScannedImport> {
+    let mut imports = Vec::new();
+    let mut lines = source.lines().enumerate();
+
+    while let Some((idx, line)) = lines.next() {
+        let code = strip_comment(line);
+        let statement = code.trim_start();
+        let column = code.len() - statement.len() + 1;
+        // Only import statements are followed to their next lines, so an unclosed parenthesis
+        // elsewhere, e.g. the syntax error itself, does not hide the imports after it.
+        let keyword = statement.split_whitespace().next();
+        if !matches!(keyword, Some("import" | "cimport" | "from")) {
+            continue;
+        }
+        let mut statement = statement.trim_end().to_string();
+
+        while is_continued(&statement) {
+            let Some((_, next)) = lines.next() else {
+                break;
+            };
+            statement = statement.trim_end_matches('\\').to_string();
+            statement.push(' ');
+            statement.push_str(strip_comment(next).trim());
+        }
+
+        imports.extend(
+            statement_paths(&statement)
+                .into_iter()
+                .map(|path| ScannedImport {
+                    path,
+                    line: idx,
+                    column,
+                }),
+        );
+    }
+    imports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_imports() {
+        // Python 2 leftovers the parser rejects.
+        let source = r#"import os, sys
+print "starting"
+
+def main():
+    from requests import (
+        get,  # for the API
+        post,
+    )
+    from . import utils
+    exec "import not_scanned"
+    x = (
+import yaml
+"#;
+
+        let scanned = scan_imports(source);
+        let imports: Vec<(&str, usize, usize)> = scanned
+            .iter()
+            .map(|import| (import.path.as_str(), import.line, import.column))
+            .collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("os", 0, 1),
+                ("sys", 0, 1),
+                ("requests.get", 4, 5),
+                ("requests.post", 4, 5),
+                ("yaml", 11, 1),
+            ]
+        );
+    }
+}
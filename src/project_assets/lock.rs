@@ -0,0 +1,228 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::project_assets::aliases::known_aliases;
+use crate::project_assets::pep508::parse_requirement;
+use crate::project_assets::{Package, PackageBuilder, PackageName};
+
+/// The name of a locked dependency edge.
+///  e.g. `certifi = ">=2017.4.17"` (poetry.lock, as a table key),
+///  e.g. `{ name = "certifi" }` (uv.lock),
+///  e.g. `"certifi>=2017.4.17"` (pdm.lock)
+fn requirement_name(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(requirement) => parse_requirement(requirement)
+            .map(|requirement| requirement.name)
+            .ok(),
+        toml::Value::Table(table) => table
+            .get("name")
+            .and_then(toml::Value::as_str)
+            .map(ToString::to_string),
+        _ => None,
+    }
+}
+
+/// The project itself is locked by uv as an editable or virtual package at `.`.
+fn is_project_itself(package: &toml::value::Table) -> bool {
+    let Some(toml::Value::Table(source)) = package.get("source") else {
+        return false;
+    };
+    ["editable", "virtual"]
+        .iter()
+        .any(|kind| source.get(*kind).and_then(toml::Value::as_str) == Some("."))
+}
+
+/// Builds the packages of a project from the `[[package]]` entries of its lock file, instead
+/// of the installed site-packages. `poetry.lock`, `uv.lock` and `pdm.lock` are supported.
+///
+/// Nothing is installed, so package sizes are unknown and import names come from a bundled
+/// mapping of distribution names.
+pub fn get_lock_packages(lock_file: &Path) -> Result<HashSet<Package>> {
+    let toml_str = fs::read_to_string(lock_file)
+        .with_context(|| format!("Failed to read lock file at {:?}", lock_file))?;
+
+    let toml_value: toml::Value =
+        toml::from_str(&toml_str).with_context(|| "Failed to parse TOML content")?;
+
+    // A package can be locked several times, once per resolution fork (e.g. per Python version).
+    let mut requirements: HashMap<PackageName, HashSet<String>> = HashMap::new();
+
+    let Some(toml::Value::Array(packages)) = toml_value.get("package") else {
+        return Ok(HashSet::new());
+    };
+    for package in packages.iter().filter_map(toml::Value::as_table) {
+        let Some(name) = package.get("name").and_then(toml::Value::as_str) else {
+            continue;
+        };
+        if is_project_itself(package) {
+            continue;
+        }
+
+        let edges = requirements.entry(PackageName::new(name)).or_default();
+        match package.get("dependencies") {
+            Some(toml::Value::Table(dependencies)) => edges.extend(dependencies.keys().cloned()),
+            Some(toml::Value::Array(dependencies)) => {
+                edges.extend(dependencies.iter().filter_map(requirement_name))
+            }
+            _ => (),
+        }
+    }
+
+    Ok(requirements
+        .into_iter()
+        .map(|(name, requirements)| {
+            let aliases = known_aliases(&name);
+            // Without entry points, a package is assumed to install a command of its own name,
+            // e.g. `black` or `gunicorn`.
+            let commands = HashSet::from([name.to_string()]);
+            PackageBuilder::new(name.to_string(), aliases, 0, requirements)
+                .commands(commands)
+                .build()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Helper function to find a package by id.
+    fn find<'a>(packages: &'a HashSet<Package>, id: &str) -> &'a Package {
+        packages
+            .iter()
+            .find(|pkg| pkg.id() == id)
+            .expect("Package not found")
+    }
+
+    #[test]
+    fn parse_poetry_lock() {
+        let temp_dir = tempdir().unwrap();
+        let lock_file = temp_dir.path().join("poetry.lock");
+        fs::write(
+            &lock_file,
+            r#"
+[[package]]
+name = "requests"
+version = "2.31.0"
+optional = false
+python-versions = ">=3.7"
+files = []
+
+[package.dependencies]
+certifi = ">=2017.4.17"
+charset-normalizer = ">=2,<4"
+PySocks = {version = ">=1.5.6,!=1.5.7", optional = true, markers = "extra == \"socks\""}
+
+[[package]]
+name = "PyYAML"
+version = "6.0.1"
+optional = false
+python-versions = ">=3.6"
+files = []
+
+[metadata]
+lock-version = "2.0"
+"#,
+        )
+        .unwrap();
+
+        let packages = get_lock_packages(&lock_file).expect("Failed to get packages");
+
+        assert_eq!(packages.len(), 2);
+        let requests = find(&packages, "requests");
+        assert!(requests.aliases().contains("requests"));
+        assert!(requests.requirements().contains("charset-normalizer"));
+        assert!(requests.requirements().contains("pysocks"));
+        assert_eq!(requests.size(), 0);
+        assert!(find(&packages, "pyyaml").aliases().contains("yaml"));
+    }
+
+    #[test]
+    fn parse_uv_lock() {
+        let temp_dir = tempdir().unwrap();
+        let lock_file = temp_dir.path().join("uv.lock");
+        fs::write(
+            &lock_file,
+            r#"
+version = 1
+requires-python = ">=3.12"
+
+[[package]]
+name = "my-project"
+version = "0.1.0"
+source = { editable = "." }
+dependencies = [{ name = "httpx" }]
+
+[[package]]
+name = "httpx"
+version = "0.27.0"
+source = { registry = "https://pypi.org/simple" }
+dependencies = [
+    { name = "anyio" },
+    { name = "certifi" },
+]
+
+[[package]]
+name = "anyio"
+version = "4.4.0"
+source = { registry = "https://pypi.org/simple" }
+"#,
+        )
+        .unwrap();
+
+        let packages = get_lock_packages(&lock_file).expect("Failed to get packages");
+
+        let ids: HashSet<&str> = packages.iter().map(Package::id).collect();
+        assert_eq!(ids, HashSet::from(["httpx", "anyio"]));
+        let httpx = find(&packages, "httpx");
+        assert!(httpx.requirements().contains("anyio"));
+        assert!(httpx.requirements().contains("certifi"));
+    }
+
+    #[test]
+    fn parse_pdm_lock() {
+        let temp_dir = tempdir().unwrap();
+        let lock_file = temp_dir.path().join("pdm.lock");
+        fs::write(
+            &lock_file,
+            r#"
+[metadata]
+groups = ["default"]
+
+[[package]]
+name = "python-dateutil"
+version = "2.9.0"
+groups = ["default"]
+dependencies = [
+    "six>=1.5",
+]
+
+[[package]]
+name = "six"
+version = "1.16.0"
+groups = ["default"]
+"#,
+        )
+        .unwrap();
+
+        let packages = get_lock_packages(&lock_file).expect("Failed to get packages");
+
+        let dateutil = find(&packages, "python-dateutil");
+        assert!(dateutil.aliases().contains("dateutil"));
+        assert!(dateutil.requirements().contains("six"));
+        assert!(find(&packages, "six").requirements().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_lock_file() {
+        let temp_dir = tempdir().unwrap();
+        let lock_file = temp_dir.path().join("poetry.lock");
+        fs::write(&lock_file, "invalid toml content").unwrap();
+
+        assert!(get_lock_packages(&lock_file).is_err());
+    }
+}
@@ -1,9 +1,46 @@
+mod aliases;
+mod cache;
+mod config_usage;
+mod cython;
 mod dependency;
+mod encoding;
+mod first_party;
 mod import;
+mod line_scan;
+mod lock;
+mod notebook;
 mod package;
+mod pep503;
+mod pep508;
+mod pep621;
+mod pep735;
+mod pipfile;
+mod setuptools;
+mod stdlib;
+#[cfg(test)]
+mod test_utils;
+mod tool_config;
+mod walk;
 
+pub(crate) use cache::clear_cache;
+pub(crate) use config_usage::{get_config_usages, ConfigUsage};
 #[allow(unused_imports)]
-pub(crate) use dependency::{get_dependencies, Dependency, DependencyBuilder};
-pub(crate) use import::get_imports;
+pub(crate) use dependency::{
+    get_constraint_files, get_dependencies, spec_file_type, Dependency, DependencyBuilder, Origin,
+    Source, VersionConflict, MAIN_GROUP,
+};
+pub(crate) use first_party::get_first_party;
+#[allow(unused_imports)]
+pub(crate) use import::{
+    get_imports, FileError, FileErrorKind, Import, ImportContext, ImportKind, Location,
+};
+pub(crate) use lock::get_lock_packages;
 #[allow(unused_imports)]
 pub(crate) use package::{get_packages, get_site_packages, Package, PackageBuilder, PackageState};
+#[allow(unused_imports)]
+pub(crate) use pep503::PackageName;
+pub(crate) use stdlib::{
+    find_backports, get_target_python, is_stdlib_module, Backport, PythonVersion,
+};
+pub(crate) use tool_config::get_tool_config;
+pub(crate) use walk::TestFiles;
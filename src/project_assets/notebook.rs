@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+
+/// Replace the IPython-only lines of a cell so the rest of it parses as Python.
+/// Magics (`%time f()`), shell commands (`!pip install x`, `files = !ls`) and help requests
+/// (`np.array?`) become `pass` at the same indentation, which keeps blocks and line numbers intact.
+/// Cell magics (`%%bash`) make the whole cell non-Python.
+fn strip_magics(cell: &str) -> Option<String> {
+    if cell.trim_start().starts_with("%%") {
+        return None;
+    }
+
+    let lines: Vec<String> = cell
+        .lines()
+        .map(|line| {
+            let code = line.trim_start();
+            let indentation = &line[..line.len() - code.len()];
+            let code_end = code.trim_end();
+            let is_help = code_end.ends_with('?')
+                && code_end
+                    .trim_end_matches('?')
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+            let is_magic = code.starts_with(['%', '!', '?'])
+                || is_help
+                || code
+                    .split_once('=')
+                    .is_some_and(|(_, value)| value.trim_start().starts_with(['%', '!']));
+            if is_magic {
+                format!("{}pass", indentation)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    Some(lines.join("\n"))
+}
+
+/// The source of a cell is either a string or a list of lines.
+fn cell_source(cell: &serde_json::Value) -> String {
+    match cell.get("source") {
+        Some(serde_json::Value::String(source)) => source.clone(),
+        Some(serde_json::Value::Array(lines)) => {
+            lines.iter().filter_map(|line| line.as_str()).collect()
+        }
+        _ => String::new(),
+    }
+}
+
+/// Parses the notebook JSON and returns the Python source of its code cells, with the index of
+/// each cell in the notebook.
+pub fn notebook_cells(content: &str) -> Result<Vec<(usize, String)>> {
+    let notebook: serde_json::Value =
+        serde_json::from_str(content).with_context(|| "Failed to parse notebook JSON")?;
+
+    let cells = notebook
+        .get("cells")
+        .and_then(|cells| cells.as_array())
+        .context("Notebook has no cells")?;
+
+    Ok(cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.get("cell_type").and_then(|t| t.as_str()) == Some("code"))
+        .filter_map(|(idx, cell)| Some((idx, strip_magics(&cell_source(cell))?)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_magics() {
+        assert_eq!(
+            strip_magics("%matplotlib inline\nimport numpy as np\n!pip install pandas"),
+            Some("pass\nimport numpy as np\npass".to_string())
+        );
+        assert_eq!(
+            strip_magics("for f in files:\n    %time load(f)\nfiles = !ls\nnp.array?"),
+            Some("for f in files:\n    pass\npass\npass".to_string())
+        );
+        assert_eq!(
+            strip_magics("import scipy  # needed?"),
+            Some("import scipy  # needed?".to_string())
+        );
+        assert_eq!(strip_magics("%%bash\npip install pandas"), None);
+    }
+
+    #[test]
+    fn test_notebook_cells() {
+        let notebook = r##"{
+            "cells": [
+                { "cell_type": "markdown", "source": ["# Title"] },
+                { "cell_type": "code", "source": ["import pandas as pd\n", "%load_ext autoreload"] },
+                { "cell_type": "code", "source": "from sklearn import svm" }
+            ],
+            "metadata": {},
+            "nbformat": 4,
+            "nbformat_minor": 5
+        }"##;
+
+        assert_eq!(
+            notebook_cells(notebook).unwrap(),
+            vec![
+                (1, "import pandas as pd\npass".to_string()),
+                (2, "from sklearn import svm".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_notebook() {
+        assert!(notebook_cells("not json").is_err());
+        assert!(notebook_cells("{}").is_err());
+    }
+}
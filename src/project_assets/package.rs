@@ -17,7 +17,10 @@ use glob::glob;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-#[derive(clap::ValueEnum, Debug, PartialEq, Eq, Clone, Hash)]
+use crate::project_assets::PackageName;
+
+#[derive(clap::ValueEnum, Serialize, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[serde(rename_all = "lowercase")]
 pub enum PackageState {
     /// The dependency is installed, actively used in the project, and correctly listed in pyproject.toml.
     /// This state indicates a fully integrated and properly managed dependency.
@@ -33,10 +36,12 @@ pub enum PackageState {
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct Package {
-    id: String,
+    id: PackageName,
     size: u64,
     aliases: HashSet<String>,
-    requirements: HashSet<String>,
+    requirements: HashSet<PackageName>,
+    /// The commands the package installs, from its `console_scripts` and `gui_scripts`.
+    commands: HashSet<String>,
 }
 
 impl Hash for Package {
@@ -47,6 +52,10 @@ impl Hash for Package {
 
 impl Package {
     pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn name(&self) -> &PackageName {
         &self.id
     }
 
@@ -58,17 +67,22 @@ impl Package {
         self.size
     }
 
-    pub fn requirements(&self) -> &HashSet<String> {
+    pub fn requirements(&self) -> &HashSet<PackageName> {
         &self.requirements
     }
+
+    pub fn commands(&self) -> &HashSet<String> {
+        &self.commands
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct PackageBuilder {
-    id: String,
+    id: PackageName,
     size: u64,
     aliases: HashSet<String>,
-    requirements: HashSet<String>,
+    requirements: HashSet<PackageName>,
+    commands: HashSet<String>,
 }
 
 impl PackageBuilder {
@@ -78,22 +92,28 @@ impl PackageBuilder {
         size: u64,
         requirements: HashSet<String>,
     ) -> Self {
-        let id = id.replace('_', "-").to_lowercase();
-        let requirements = requirements.into_iter().map(|s| s.to_lowercase()).collect();
+        let requirements = requirements.into_iter().map(PackageName::from).collect();
         Self {
-            id,
+            id: PackageName::new(&id),
             size,
             aliases,
             requirements,
+            commands: HashSet::new(),
         }
     }
 
+    pub fn commands(mut self, commands: HashSet<String>) -> Self {
+        self.commands = commands;
+        self
+    }
+
     pub fn build(self) -> Package {
         Package {
             id: self.id,
             size: self.size,
             aliases: self.aliases,
             requirements: self.requirements,
+            commands: self.commands,
         }
     }
 }
@@ -125,6 +145,26 @@ pub fn get_site_packages() -> Result<HashSet<PathBuf>> {
     Ok(pkg_paths)
 }
 
+/// Reads the commands a package installs from the `entry_points.txt` of its dist-info or
+/// egg-info directory, if it has one.
+///  e.g. `black = black:patched_main` under `[console_scripts]` -> `black`
+fn read_commands(entry: &Path) -> HashSet<String> {
+    let Ok(entry_points) = fs::read_to_string(entry.join("entry_points.txt")) else {
+        return HashSet::new();
+    };
+
+    let mut in_scripts = false;
+    let mut commands = HashSet::new();
+    for line in entry_points.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            in_scripts = matches!(section.trim(), "console_scripts" | "gui_scripts");
+        } else if let Some((name, _)) = line.split_once('=').filter(|_| in_scripts) {
+            commands.insert(name.trim().to_string());
+        }
+    }
+    commands
+}
+
 /// Process the METADATA and RECORD files in the dist-info directory to extract package information.
 fn process_dist_info(entry: &Path) -> Result<Package> {
     let metadata_path = entry.join("METADATA");
@@ -185,7 +225,9 @@ fn process_dist_info(entry: &Path) -> Result<Package> {
         .map(|potential_path| get_size(potential_path).unwrap_or(0))
         .sum();
 
-    Ok(PackageBuilder::new(pkg_id, aliases, size, requirements).build())
+    Ok(PackageBuilder::new(pkg_id, aliases, size, requirements)
+        .commands(read_commands(entry))
+        .build())
 }
 
 /// Process the PKG-INFO and top_level.txt files in the egg-info directory to extract package information.
@@ -233,7 +275,9 @@ fn process_egg_info(entry: &Path) -> Result<Package> {
         .map(|potential_path| get_size(potential_path).unwrap_or(0))
         .sum();
 
-    Ok(PackageBuilder::new(pkg_id, aliases, size, requirements).build())
+    Ok(PackageBuilder::new(pkg_id, aliases, size, requirements)
+        .commands(read_commands(entry))
+        .build())
 }
 
 /// This function determines the packages installed in the site-packages directory.
@@ -311,6 +355,30 @@ mod tests {
         assert!(package.aliases.contains("successful_package"));
     }
 
+    #[test]
+    fn test_read_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        create_info_dir(
+            &temp_dir,
+            "black",
+            "dist-info",
+            vec![
+                ("METADATA", Some("Name: black\nVersion: 24.1.0")),
+                ("RECORD", Some("black/__init__.py,,")),
+                (
+                    "entry_points.txt",
+                    Some("[console_scripts]\nblack = black:patched_main\nblackd = blackd:patched_main [d]\n\n[pytest11]\nblack_plugin = black.plugin"),
+                ),
+            ],
+        );
+
+        let package = process_dist_info(&temp_dir.path().join("black-0.1.dist-info")).unwrap();
+        assert_eq!(
+            package.commands(),
+            &HashSet::from(["black".to_string(), "blackd".to_string()])
+        );
+    }
+
     #[test]
     fn test_process_egg_info_successful() {
         let temp_dir = TempDir::new().unwrap();
@@ -411,8 +479,8 @@ mod tests {
         assert_eq!(package.id, "successful-egg-with-requirements");
         assert!(package.aliases.contains("successful_egg_with_requirements"));
 
-        assert!(package.requirements.contains("package_one"));
-        assert!(package.requirements.contains("package_two"));
+        assert!(package.requirements.contains("package-one"));
+        assert!(package.requirements.contains("package-two"));
     }
 
     #[test]
@@ -444,7 +512,7 @@ mod tests {
             .aliases
             .contains("successful_dist_with_requirements"));
 
-        assert!(package.requirements.contains("package_one"));
-        assert!(package.requirements.contains("package_two"));
+        assert!(package.requirements.contains("package-one"));
+        assert!(package.requirements.contains("package-two"));
     }
 }
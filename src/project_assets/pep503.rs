@@ -0,0 +1,102 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A distribution name in its PEP 503 normalized form.
+/// Runs of `-`, `_` and `.` collapse to a single `-` and the name is lowercased,
+///  e.g. `Typing_Extensions`, `typing.extensions` and `typing-extensions` are the same name.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+#[serde(from = "String", into = "String")]
+pub struct PackageName(String);
+
+impl PackageName {
+    pub fn new(name: &str) -> Self {
+        let mut normalized = String::with_capacity(name.len());
+        let mut in_separator_run = false;
+        for c in name.trim().chars() {
+            if matches!(c, '-' | '_' | '.') {
+                if !in_separator_run {
+                    normalized.push('-');
+                }
+                in_separator_run = true;
+            } else {
+                normalized.extend(c.to_lowercase());
+                in_separator_run = false;
+            }
+        }
+        Self(normalized)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for PackageName {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for PackageName {
+    fn from(name: String) -> Self {
+        Self::new(&name)
+    }
+}
+
+impl From<PackageName> for String {
+    fn from(name: PackageName) -> Self {
+        name.0
+    }
+}
+
+impl Borrow<str> for PackageName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for PackageName {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for PackageName {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Display for PackageName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization() {
+        assert_eq!(PackageName::new("requests"), "requests");
+        assert_eq!(PackageName::new("Django"), "django");
+        assert_eq!(PackageName::new("typing_extensions"), "typing-extensions");
+        assert_eq!(PackageName::new("zope.interface"), "zope-interface");
+        assert_eq!(PackageName::new("Foo__Bar-._baz"), "foo-bar-baz");
+        assert_eq!(
+            PackageName::new("Typing.Extensions"),
+            PackageName::new("typing_extensions")
+        );
+    }
+
+    #[test]
+    fn test_serialize_as_string() {
+        let name = PackageName::new("Ruamel.YAML");
+        assert_eq!(serde_json::to_string(&name).unwrap(), "\"ruamel-yaml\"");
+        let name: PackageName = serde_json::from_str("\"Ruamel_YAML\"").unwrap();
+        assert_eq!(name, "ruamel-yaml");
+    }
+}
@@ -0,0 +1,343 @@
+use std::fmt;
+
+use anyhow::{bail, Result};
+
+/// The comparison operator of a version specifier, e.g. the `>=` in `>=2.31`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Operator {
+    Compatible,
+    ArbitraryEqual,
+    Equal,
+    NotEqual,
+    LessEqual,
+    GreaterEqual,
+    Less,
+    Greater,
+}
+
+impl Operator {
+    /// Two character operators must be tried before their one character prefixes.
+    const ALL: [(&'static str, Operator); 8] = [
+        ("===", Operator::ArbitraryEqual),
+        ("~=", Operator::Compatible),
+        ("==", Operator::Equal),
+        ("!=", Operator::NotEqual),
+        ("<=", Operator::LessEqual),
+        (">=", Operator::GreaterEqual),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(_, op)| op == self)
+            .map(|(s, _)| *s)
+            .unwrap_or_default()
+    }
+}
+
+/// A single version clause, e.g. `>=2.31`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct VersionSpecifier {
+    pub operator: Operator,
+    pub version: String,
+}
+
+impl fmt::Display for VersionSpecifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.operator.as_str(), self.version)
+    }
+}
+
+/// A parsed PEP 508 dependency specification.
+///  e.g. `requests[socks] >=2.31, <3 ; python_version < "3.12"`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Requirement {
+    pub name: String,
+    pub extras: Vec<String>,
+    pub specifiers: Vec<VersionSpecifier>,
+    pub url: Option<String>,
+    pub marker: Option<String>,
+}
+
+impl Requirement {
+    /// The specifier set joined the way it is usually written, e.g. `>=2.31,<3`
+    pub fn specifier_set(&self) -> Option<String> {
+        if self.specifiers.is_empty() {
+            return None;
+        }
+        let specifiers: Vec<String> = self.specifiers.iter().map(|s| s.to_string()).collect();
+        Some(specifiers.join(","))
+    }
+}
+
+/// A minimal cursor over the requirement string
+struct Cursor<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let end = rest.find(|c: char| !predicate(c)).unwrap_or(rest.len());
+        self.pos += end;
+        &rest[..end]
+    }
+
+    fn is_done(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
+fn is_version_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '*' | '+' | '!')
+}
+
+fn parse_identifier(cursor: &mut Cursor, what: &str) -> Result<String> {
+    let identifier = cursor.take_while(is_identifier_char);
+    let valid = identifier
+        .chars()
+        .next()
+        .zip(identifier.chars().last())
+        .is_some_and(|(first, last)| first.is_ascii_alphanumeric() && last.is_ascii_alphanumeric());
+
+    if !valid {
+        bail!("expected {} at position {}", what, cursor.pos);
+    }
+    Ok(identifier.to_string())
+}
+
+fn parse_extras(cursor: &mut Cursor) -> Result<Vec<String>> {
+    let mut extras = Vec::new();
+    if !cursor.eat('[') {
+        return Ok(extras);
+    }
+
+    cursor.eat_whitespace();
+    if cursor.eat(']') {
+        return Ok(extras);
+    }
+
+    loop {
+        cursor.eat_whitespace();
+        extras.push(parse_identifier(cursor, "an extra name")?);
+        cursor.eat_whitespace();
+        if cursor.eat(']') {
+            return Ok(extras);
+        }
+        if !cursor.eat(',') {
+            bail!("expected `,` or `]` at position {}", cursor.pos);
+        }
+    }
+}
+
+fn parse_specifiers(cursor: &mut Cursor) -> Result<Vec<VersionSpecifier>> {
+    let parenthesized = cursor.eat('(');
+    let mut specifiers = Vec::new();
+
+    loop {
+        cursor.eat_whitespace();
+        let Some((symbol, operator)) = Operator::ALL
+            .iter()
+            .find(|(symbol, _)| cursor.rest().starts_with(symbol))
+        else {
+            if specifiers.is_empty() && !parenthesized {
+                return Ok(specifiers);
+            }
+            bail!("expected a version operator at position {}", cursor.pos);
+        };
+        cursor.pos += symbol.len();
+
+        cursor.eat_whitespace();
+        let version = cursor.take_while(is_version_char);
+        if version.is_empty() {
+            bail!("expected a version after `{}`", symbol);
+        }
+        specifiers.push(VersionSpecifier {
+            operator: *operator,
+            version: version.to_string(),
+        });
+
+        cursor.eat_whitespace();
+        if !cursor.eat(',') {
+            break;
+        }
+    }
+
+    if parenthesized && !cursor.eat(')') {
+        bail!("expected `)` at position {}", cursor.pos);
+    }
+    Ok(specifiers)
+}
+
+fn parse_marker(cursor: &mut Cursor) -> Result<Option<String>> {
+    if !cursor.eat(';') {
+        return Ok(None);
+    }
+
+    let marker = cursor.rest().trim();
+    if marker.is_empty() {
+        bail!("expected an environment marker after `;`");
+    }
+    cursor.pos = cursor.input.len();
+    Ok(Some(marker.to_string()))
+}
+
+/// Parses a single PEP 508 dependency specification.
+pub fn parse_requirement(input: &str) -> Result<Requirement> {
+    let mut cursor = Cursor { input, pos: 0 };
+
+    cursor.eat_whitespace();
+    let name = parse_identifier(&mut cursor, "a package name")?;
+    cursor.eat_whitespace();
+    let extras = parse_extras(&mut cursor)?;
+    cursor.eat_whitespace();
+
+    let mut url = None;
+    let mut specifiers = Vec::new();
+    if cursor.eat('@') {
+        cursor.eat_whitespace();
+        let reference = cursor.take_while(|c| !c.is_whitespace());
+        if reference.is_empty() {
+            bail!("expected a URL after `@`");
+        }
+        url = Some(reference.to_string());
+        // A marker after a URL must be separated by whitespace, as `;` is valid inside URLs.
+        let before = cursor.pos;
+        cursor.eat_whitespace();
+        if !cursor.is_done() && cursor.pos == before {
+            bail!("expected whitespace after the URL");
+        }
+    } else {
+        specifiers = parse_specifiers(&mut cursor)?;
+    }
+
+    cursor.eat_whitespace();
+    let marker = parse_marker(&mut cursor)?;
+
+    cursor.eat_whitespace();
+    if !cursor.is_done() {
+        bail!(
+            "unexpected `{}` at position {}",
+            cursor.rest().trim(),
+            cursor.pos
+        );
+    }
+
+    Ok(Requirement {
+        name,
+        extras,
+        specifiers,
+        url,
+        marker,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bare_name() {
+        let req = parse_requirement("requests").unwrap();
+        assert_eq!(req.name, "requests");
+        assert!(req.extras.is_empty());
+        assert!(req.specifiers.is_empty());
+        assert_eq!(req.specifier_set(), None);
+        assert_eq!(req.url, None);
+        assert_eq!(req.marker, None);
+    }
+
+    #[test]
+    fn test_parse_specifiers() {
+        let req = parse_requirement("Django >= 4.2, < 5.0").unwrap();
+        assert_eq!(req.name, "Django");
+        assert_eq!(req.specifier_set(), Some(">=4.2,<5.0".to_string()));
+
+        let req = parse_requirement("attrs~=23.1").unwrap();
+        assert_eq!(req.specifiers[0].operator, Operator::Compatible);
+
+        let req = parse_requirement("pywin32 !=306, ===305.post1").unwrap();
+        assert_eq!(req.specifier_set(), Some("!=306,===305.post1".to_string()));
+
+        let req = parse_requirement("zope.interface (>=5.0)").unwrap();
+        assert_eq!(req.name, "zope.interface");
+        assert_eq!(req.specifier_set(), Some(">=5.0".to_string()));
+
+        let req = parse_requirement("numpy==1.26.*").unwrap();
+        assert_eq!(req.specifier_set(), Some("==1.26.*".to_string()));
+    }
+
+    #[test]
+    fn test_parse_extras_and_marker() {
+        let req = parse_requirement("requests[socks, security]>=2.31 ; python_version < \"3.12\"")
+            .unwrap();
+        assert_eq!(req.name, "requests");
+        assert_eq!(req.extras, vec!["socks", "security"]);
+        assert_eq!(req.specifier_set(), Some(">=2.31".to_string()));
+        assert_eq!(req.marker, Some("python_version < \"3.12\"".to_string()));
+
+        let req = parse_requirement("uvicorn[]; sys_platform != 'win32'").unwrap();
+        assert!(req.extras.is_empty());
+        assert_eq!(req.marker, Some("sys_platform != 'win32'".to_string()));
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let req = parse_requirement("pip @ https://github.com/pypa/pip/archive/1.3.1.zip").unwrap();
+        assert_eq!(req.name, "pip");
+        assert_eq!(
+            req.url,
+            Some("https://github.com/pypa/pip/archive/1.3.1.zip".to_string())
+        );
+
+        let req = parse_requirement("foo @ git+https://example.com/foo.git;v=1 ; os_name == 'nt'")
+            .unwrap();
+        assert_eq!(
+            req.url,
+            Some("git+https://example.com/foo.git;v=1".to_string())
+        );
+        assert_eq!(req.marker, Some("os_name == 'nt'".to_string()));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse_requirement("").is_err());
+        assert!(parse_requirement(">=1.0").is_err());
+        assert!(parse_requirement("-e .").is_err());
+        assert!(parse_requirement("requests[socks").is_err());
+        assert!(parse_requirement("requests (>=1.0").is_err());
+        assert!(parse_requirement("requests >=").is_err());
+        assert!(parse_requirement("requests ;").is_err());
+        assert!(parse_requirement("requests 2.0").is_err());
+        assert!(parse_requirement("foo @ ").is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use crate::project_assets::pep508::parse_requirement;
+use crate::project_assets::{Dependency, DependencyBuilder, MAIN_GROUP};
+
+/// Collects the dependencies declared in a PEP 621 `[project]` table
+#[derive(Default)]
+struct Pep621Collector {
+    dependencies: HashSet<Dependency>,
+}
+
+impl Pep621Collector {
+    fn visit_project(&mut self, project: &toml::value::Table) {
+        if let Some(toml::Value::Array(requirements)) = project.get("dependencies") {
+            self.visit_requirements(MAIN_GROUP, requirements, false);
+        }
+
+        // Each optional dependency group is named after the extra it provides.
+        if let Some(toml::Value::Table(groups)) = project.get("optional-dependencies") {
+            for (group, requirements) in groups {
+                if let toml::Value::Array(requirements) = requirements {
+                    self.visit_requirements(group, requirements, true);
+                }
+            }
+        }
+    }
+
+    fn visit_requirements(&mut self, group: &str, requirements: &[toml::Value], optional: bool) {
+        for requirement in requirements.iter().filter_map(toml::Value::as_str) {
+            match parse_requirement(requirement) {
+                Ok(requirement) => {
+                    let mut builder = DependencyBuilder::from(requirement).group(group.to_string());
+                    if optional {
+                        builder = builder.optional();
+                    }
+                    self.dependencies.insert(builder.build());
+                }
+                Err(e) => eprintln!("Could not parse requirement `{}`: {:#}", requirement, e),
+            }
+        }
+    }
+}
+
+/// Reads the `[project].dependencies` and `[project.optional-dependencies]` arrays of a `pyproject.toml`.
+pub fn get_pep621_dependencies(pyproject: &toml::Value) -> HashSet<Dependency> {
+    let mut collector = Pep621Collector::default();
+
+    if let Some(toml::Value::Table(project)) = pyproject.get("project") {
+        collector.visit_project(project);
+    }
+
+    collector.dependencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_assets::test_utils::find;
+
+    #[test]
+    fn parse_project_dependencies() {
+        let pyproject: toml::Value = toml::from_str(
+            "
+            [project]
+            name = \"my_project\"
+            dependencies = [
+                \"httpx>=0.27\",
+                \"rich\",
+                \"typing_extensions ; python_version < '3.11'\",
+            ]
+                        ",
+        )
+        .unwrap();
+
+        let dependencies = get_pep621_dependencies(&pyproject);
+
+        assert_eq!(dependencies.len(), 3);
+        assert_eq!(find(&dependencies, "httpx").version(), ">=0.27");
+        assert_eq!(find(&dependencies, "rich").version(), "N/A");
+        let typing_extensions = find(&dependencies, "typing-extensions");
+        assert_eq!(typing_extensions.marker(), Some("python_version < '3.11'"));
+    }
+
+    #[test]
+    fn parse_optional_dependencies() {
+        let pyproject: toml::Value = toml::from_str(
+            "
+            [project]
+            name = \"my_project\"
+            dependencies = [\"httpx>=0.27\"]
+
+            [project.optional-dependencies]
+            test = [\"pytest>=8\", \"pytest-cov\"]
+            docs = [\"mkdocs-material[imaging]~=9.5\"]
+                        ",
+        )
+        .unwrap();
+
+        let dependencies = get_pep621_dependencies(&pyproject);
+
+        assert_eq!(dependencies.len(), 4);
+        let httpx = find(&dependencies, "httpx");
+        assert_eq!(httpx.group(), MAIN_GROUP);
+        assert!(!httpx.is_optional());
+        let pytest = find(&dependencies, "pytest");
+        assert_eq!(pytest.version(), ">=8");
+        assert_eq!(pytest.group(), "test");
+        assert!(pytest.is_optional());
+        assert_eq!(find(&dependencies, "pytest-cov").group(), "test");
+        let mkdocs_material = find(&dependencies, "mkdocs-material");
+        assert_eq!(mkdocs_material.version(), "~=9.5");
+        assert_eq!(mkdocs_material.extras(), ["imaging"]);
+        assert_eq!(mkdocs_material.group(), "docs");
+    }
+
+    #[test]
+    fn parse_without_project_table() {
+        let pyproject: toml::Value = toml::from_str(
+            "
+            [tool.poetry.dependencies]
+            package_a = \"^1.0\"
+                        ",
+        )
+        .unwrap();
+
+        let dependencies = get_pep621_dependencies(&pyproject);
+
+        assert!(dependencies.is_empty());
+    }
+}
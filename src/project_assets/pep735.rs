@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+
+use crate::project_assets::pep508::parse_requirement;
+use crate::project_assets::{Dependency, DependencyBuilder};
+
+/// Reads the PEP 735 `[dependency-groups]` table of a `pyproject.toml`.
+///
+/// `{include-group = "..."}` entries are skipped: the included group's requirements are
+/// already collected under the group that declares them.
+pub fn get_pep735_dependencies(pyproject: &toml::Value) -> HashSet<Dependency> {
+    let mut dependencies = HashSet::new();
+
+    let Some(toml::Value::Table(groups)) = pyproject.get("dependency-groups") else {
+        return dependencies;
+    };
+
+    for (group, requirements) in groups {
+        let Some(requirements) = requirements.as_array() else {
+            continue;
+        };
+        for requirement in requirements.iter().filter_map(toml::Value::as_str) {
+            match parse_requirement(requirement) {
+                Ok(requirement) => {
+                    dependencies.insert(
+                        DependencyBuilder::from(requirement)
+                            .group(group.to_string())
+                            .build(),
+                    );
+                }
+                Err(e) => eprintln!("Could not parse requirement `{}`: {:#}", requirement, e),
+            }
+        }
+    }
+
+    dependencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_dependency_groups() {
+        let pyproject: toml::Value = toml::from_str(
+            r#"
+[project]
+name = "my_project"
+dependencies = ["httpx"]
+
+[dependency-groups]
+test = ["pytest>=8", "coverage[toml]", { include-group = "typing" }]
+typing = ["mypy==1.9.0"]
+"#,
+        )
+        .unwrap();
+
+        let dependencies = get_pep735_dependencies(&pyproject);
+
+        let mut groups: Vec<(&str, &str)> = dependencies
+            .iter()
+            .map(|dep| (dep.group(), dep.id()))
+            .collect();
+        groups.sort();
+        assert_eq!(
+            groups,
+            vec![("test", "coverage"), ("test", "pytest"), ("typing", "mypy")]
+        );
+    }
+
+    #[test]
+    fn parse_without_dependency_groups() {
+        let pyproject: toml::Value = toml::from_str("[project]\nname = \"my_project\"\n").unwrap();
+
+        let dependencies = get_pep735_dependencies(&pyproject);
+
+        assert!(dependencies.is_empty());
+    }
+}
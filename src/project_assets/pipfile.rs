@@ -0,0 +1,280 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::project_assets::{
+    Dependency, DependencyBuilder, Origin, PackageName, Source, MAIN_GROUP,
+};
+
+/// Top-level Pipfile tables that do not hold packages.
+const NON_PACKAGE_TABLES: [&str; 4] = ["source", "requires", "scripts", "pipenv"];
+
+/// Map a Pipfile package category to its dependency group.
+///  e.g. `packages` -> `main`, `dev-packages` -> `dev`, `docs` -> `docs`
+fn category_group(category: &str) -> &str {
+    match category {
+        "packages" => MAIN_GROUP,
+        "dev-packages" => "dev",
+        category => category,
+    }
+}
+
+/// Map a Pipfile.lock section to the Pipfile category it locks.
+fn lock_section_category(section: &str) -> &str {
+    match section {
+        "default" => "packages",
+        "develop" => "dev-packages",
+        section => section,
+    }
+}
+
+/// `*` means "any version" in a Pipfile
+fn pipfile_version(version: &str) -> Option<String> {
+    let version = version.trim();
+    (!version.is_empty() && version != "*").then(|| version.to_string())
+}
+
+/// Reads the pinned versions out of a Pipfile.lock, keyed by (group, package name).
+fn get_locked_versions(lock_file: &Path) -> Result<HashMap<(String, PackageName), String>> {
+    let json_str = fs::read_to_string(lock_file)
+        .with_context(|| format!("Failed to read file at {:?}", lock_file))?;
+
+    let lock: serde_json::Value =
+        serde_json::from_str(&json_str).with_context(|| "Failed to parse Pipfile.lock content")?;
+
+    let mut versions = HashMap::new();
+    for (section, packages) in lock.as_object().into_iter().flatten() {
+        if section.starts_with('_') {
+            continue;
+        }
+        let group = category_group(lock_section_category(section)).to_string();
+        for (name, package) in packages.as_object().into_iter().flatten() {
+            if let Some(version) = package.get("version").and_then(|v| v.as_str()) {
+                versions.insert((group.clone(), PackageName::new(name)), version.to_string());
+            }
+        }
+    }
+
+    Ok(versions)
+}
+
+/// Collects the dependencies declared in the package categories of a Pipfile
+struct PipfileCollector<'a> {
+    origin: Origin,
+    locked_versions: &'a HashMap<(String, PackageName), String>,
+    dependencies: HashSet<Dependency>,
+}
+
+impl<'a> PipfileCollector<'a> {
+    fn visit_category(&mut self, category: &str, packages: &toml::value::Table) {
+        let group = category_group(category);
+        for (name, value) in packages {
+            self.visit_value(group, name, value);
+        }
+    }
+
+    fn visit_value(&mut self, group: &str, name: &str, value: &toml::Value) {
+        let mut builder = DependencyBuilder::new(name.to_string())
+            .group(group.to_string())
+            .origin(self.origin.clone());
+        let version = match value {
+            // e.g. `requests = "*"`
+            toml::Value::String(spec) => pipfile_version(spec),
+            // e.g. `requests = { version = ">=2.31", extras = ["socks"], markers = "..." }`
+            toml::Value::Table(table) => {
+                if let Some(toml::Value::Array(extras)) = table.get("extras") {
+                    builder = builder.extras(
+                        extras
+                            .iter()
+                            .filter_map(toml::Value::as_str)
+                            .map(ToString::to_string)
+                            .collect(),
+                    );
+                }
+                if let Some(toml::Value::String(marker)) = table.get("markers") {
+                    builder = builder.marker(marker.clone());
+                }
+                // e.g. `foo = { git = "...", ref = "v1.0" }`, `bar = { path = "./bar", editable = true }`
+                let get = |key: &str| table.get(key).and_then(toml::Value::as_str);
+                if let Some(git) = get("git") {
+                    builder = builder.source(Source::Git(match get("ref") {
+                        Some(reference) => format!("{}@{}", git, reference),
+                        None => git.to_string(),
+                    }));
+                } else if let Some(path) = get("path") {
+                    builder = builder.source(Source::Path(path.to_string()));
+                } else if let Some(file) = get("file") {
+                    builder = builder.source(Source::from_reference(file));
+                }
+                table
+                    .get("version")
+                    .and_then(toml::Value::as_str)
+                    .and_then(pipfile_version)
+            }
+            _ => return,
+        };
+
+        let version = version.or_else(|| {
+            self.locked_versions
+                .get(&(group.to_string(), PackageName::new(name)))
+                .cloned()
+        });
+        if let Some(version) = version {
+            builder = builder.version(version);
+        }
+        self.dependencies.insert(builder.build());
+    }
+}
+
+/// Reads the package categories of a Pipfile.
+/// If a `Pipfile.lock` sits next to it, its pins are used for packages declared without a version.
+pub fn get_pipfile_dependencies(dep_spec_file: &Path) -> Result<HashSet<Dependency>> {
+    let toml_str = fs::read_to_string(dep_spec_file)
+        .with_context(|| format!("Failed to read TOML file at {:?}", dep_spec_file))?;
+
+    let toml_value: toml::Value =
+        toml::from_str(&toml_str).with_context(|| "Failed to parse TOML content")?;
+
+    let lock_file = dep_spec_file.with_file_name("Pipfile.lock");
+    // The lock is optional, so a stale or malformed one only loses the pins.
+    let locked_versions = if lock_file.exists() {
+        get_locked_versions(&lock_file).unwrap_or_else(|e| {
+            eprintln!("Ignoring {:?}: {:#}", lock_file, e);
+            HashMap::new()
+        })
+    } else {
+        HashMap::new()
+    };
+
+    let mut collector = PipfileCollector {
+        origin: Origin::new(dep_spec_file.to_path_buf(), None),
+        locked_versions: &locked_versions,
+        dependencies: HashSet::new(),
+    };
+
+    if let toml::Value::Table(table) = toml_value {
+        for (category, value) in table {
+            if NON_PACKAGE_TABLES.contains(&category.as_str()) {
+                continue;
+            }
+            if let toml::Value::Table(packages) = value {
+                collector.visit_category(&category, &packages);
+            }
+        }
+    }
+
+    Ok(collector.dependencies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_assets::test_utils::{create_file, find};
+    use tempfile::tempdir;
+
+    const PIPFILE: &str = r#"
+[[source]]
+url = "https://pypi.org/simple"
+verify_ssl = true
+name = "pypi"
+
+[packages]
+requests = "*"
+django = ">=4.2"
+uvicorn = { version = "*", extras = ["standard"], markers = "sys_platform != 'win32'" }
+
+[dev-packages]
+pytest = "*"
+mylib = { path = "./mylib", editable = true }
+
+[docs]
+mkdocs = "~=1.5"
+
+[requires]
+python_version = "3.11"
+"#;
+
+    #[test]
+    fn parse_pipfile_dependencies() {
+        let temp_dir = tempdir().unwrap();
+        let pipfile_path = create_file(&temp_dir, "Pipfile", PIPFILE);
+
+        let dependencies =
+            get_pipfile_dependencies(pipfile_path.as_path()).expect("Failed to get dependencies");
+
+        assert_eq!(dependencies.len(), 6);
+
+        let requests = find(&dependencies, "requests");
+        assert_eq!(requests.version(), "N/A");
+        assert_eq!(requests.group(), MAIN_GROUP);
+        assert_eq!(requests.origin().unwrap().file(), pipfile_path);
+
+        assert_eq!(find(&dependencies, "django").version(), ">=4.2");
+
+        let uvicorn = find(&dependencies, "uvicorn");
+        assert_eq!(uvicorn.extras(), ["standard"]);
+        assert_eq!(uvicorn.marker(), Some("sys_platform != 'win32'"));
+
+        assert_eq!(find(&dependencies, "pytest").group(), "dev");
+        assert_eq!(find(&dependencies, "mkdocs").group(), "docs");
+
+        let mylib = find(&dependencies, "mylib");
+        assert_eq!(mylib.source(), &Source::Path("./mylib".to_string()));
+        assert_eq!(mylib.group(), "dev");
+    }
+
+    #[test]
+    fn parse_pipfile_dependencies_with_lock() {
+        let temp_dir = tempdir().unwrap();
+        let pipfile_path = create_file(&temp_dir, "Pipfile", PIPFILE);
+        create_file(
+            &temp_dir,
+            "Pipfile.lock",
+            r#"{
+                    "_meta": { "hash": { "sha256": "abc" } },
+                    "default": {
+                        "requests": { "version": "==2.31.0", "hashes": [] },
+                        "django": { "version": "==4.2.11" },
+                        "certifi": { "version": "==2024.2.2" }
+                    },
+                    "develop": {
+                        "pytest": { "version": "==8.1.1" }
+                    }
+                }"#,
+        );
+
+        let dependencies =
+            get_pipfile_dependencies(pipfile_path.as_path()).expect("Failed to get dependencies");
+
+        // Locked packages that are not declared in the Pipfile are not dependencies.
+        assert_eq!(dependencies.len(), 6);
+        assert_eq!(find(&dependencies, "requests").version(), "==2.31.0");
+        assert_eq!(find(&dependencies, "pytest").version(), "==8.1.1");
+        // Declared versions are kept.
+        assert_eq!(find(&dependencies, "django").version(), ">=4.2");
+    }
+
+    #[test]
+    fn parse_pipfile_dependencies_with_invalid_lock() {
+        let temp_dir = tempdir().unwrap();
+        let pipfile_path = create_file(&temp_dir, "Pipfile", PIPFILE);
+        create_file(&temp_dir, "Pipfile.lock", "not json");
+
+        // The Pipfile is still read, without pins.
+        let dependencies =
+            get_pipfile_dependencies(pipfile_path.as_path()).expect("Failed to get dependencies");
+        assert_eq!(dependencies.len(), 6);
+        assert_eq!(find(&dependencies, "requests").version(), "N/A");
+    }
+
+    #[test]
+    fn test_invalid_pipfile() {
+        let temp_dir = tempdir().unwrap();
+        let pipfile_path = temp_dir.path().join("Pipfile");
+        fs::write(&pipfile_path, "invalid toml content").unwrap();
+
+        assert!(get_pipfile_dependencies(pipfile_path.as_path()).is_err());
+    }
+}
@@ -0,0 +1,413 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use rustpython_parser::source_code::LineIndex;
+use rustpython_parser::{ast, ast::Visitor, parse, Mode};
+
+use crate::project_assets::dependency::strip_comment;
+use crate::project_assets::pep508::parse_requirement;
+use crate::project_assets::{Dependency, DependencyBuilder, Origin, MAIN_GROUP};
+
+/// Collects requirement strings together with the group and line they were declared on.
+struct SetuptoolsCollector {
+    file: PathBuf,
+    dependencies: HashSet<Dependency>,
+}
+
+impl SetuptoolsCollector {
+    fn new(file: &Path) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            dependencies: HashSet::new(),
+        }
+    }
+
+    /// `extras_require` keys may carry a marker, e.g. `test:python_version < "3.8"`.
+    /// An empty extra name means the marker applies to `install_requires`.
+    fn visit_extra(&mut self, extra: &str, requirements: &[(String, usize)]) {
+        let (group, marker) = match extra.split_once(':') {
+            Some((group, marker)) => (group.trim(), Some(marker.trim())),
+            None => (extra.trim(), None),
+        };
+        let group = if group.is_empty() { MAIN_GROUP } else { group };
+
+        for (requirement, line) in requirements {
+            self.visit_requirement(group, marker, requirement, *line);
+        }
+    }
+
+    fn visit_requirement(
+        &mut self,
+        group: &str,
+        marker: Option<&str>,
+        requirement: &str,
+        line: usize,
+    ) {
+        match parse_requirement(requirement) {
+            Ok(requirement) => {
+                let mut builder = DependencyBuilder::from(requirement)
+                    .group(group.to_string())
+                    .origin(Origin::new(self.file.clone(), Some(line)));
+                if let Some(marker) = marker {
+                    builder = builder.marker(marker.to_string());
+                }
+                // Every group but the main one comes from `extras_require`.
+                if group != MAIN_GROUP {
+                    builder = builder.optional();
+                }
+                self.dependencies.insert(builder.build());
+            }
+            Err(e) => eprintln!(
+                "Could not parse requirement `{}` on line {} of {:?}: {:#}",
+                requirement, line, self.file, e
+            ),
+        }
+    }
+}
+
+/// A value of an option in `setup.cfg`, as the lines it spans.
+type CfgValue = Vec<(usize, String)>;
+
+/// A minimal reader for the INI dialect `setup.cfg` is written in.
+/// Returns the lines of every option, keyed by (section, option).
+/// Comments start with `#` or `;`, inline ones after whitespace, like configparser's
+/// `inline_comment_prefixes`.
+fn parse_setup_cfg(content: &str) -> HashMap<(String, String), CfgValue> {
+    let mut options: HashMap<(String, String), CfgValue> = HashMap::new();
+    let mut section = String::new();
+    let mut current: Option<(String, String)> = None;
+
+    for (idx, line) in content.lines().enumerate() {
+        let line = strip_comment(line, &['#', ';']);
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        // Indented lines continue the value of the previous option.
+        if line.starts_with(char::is_whitespace) {
+            if let Some(value) = current.as_ref().and_then(|key| options.get_mut(key)) {
+                value.push((idx + 1, trimmed.to_string()));
+            }
+            continue;
+        }
+
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            section = name.trim().to_string();
+            current = None;
+            continue;
+        }
+
+        if let Some(idx_sep) = trimmed.find(['=', ':']) {
+            let key = (section.clone(), trimmed[..idx_sep].trim().to_lowercase());
+            let value = trimmed[idx_sep + 1..].trim();
+            let lines = if value.is_empty() {
+                Vec::new()
+            } else {
+                vec![(idx + 1, value.to_string())]
+            };
+            options.insert(key.clone(), lines);
+            current = Some(key);
+        }
+    }
+
+    options
+}
+
+/// Split an option value into its items the way setuptools does:
+/// a multi-line value has one item per line, a single line is separated by `;`.
+fn cfg_list(value: &CfgValue) -> Vec<(String, usize)> {
+    match value.as_slice() {
+        [(line, single)] => single
+            .split(';')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| (item.to_string(), *line))
+            .collect(),
+        lines => lines
+            .iter()
+            .map(|(line, item)| (item.clone(), *line))
+            .collect(),
+    }
+}
+
+/// Reads `[options] install_requires` and `[options.extras_require]` from a `setup.cfg`.
+pub fn get_setup_cfg_dependencies(dep_spec_file: &Path) -> Result<HashSet<Dependency>> {
+    let content = fs::read_to_string(dep_spec_file)
+        .with_context(|| format!("Failed to read file at {:?}", dep_spec_file))?;
+
+    let options = parse_setup_cfg(&content);
+    let mut collector = SetuptoolsCollector::new(dep_spec_file);
+
+    for ((section, key), value) in &options {
+        match (section.as_str(), key.as_str()) {
+            ("options", "install_requires") => collector.visit_extra("", &cfg_list(value)),
+            ("options.extras_require", extra) => collector.visit_extra(extra, &cfg_list(value)),
+            _ => (),
+        }
+    }
+
+    Ok(collector.dependencies)
+}
+
+/// A literal value that can be read from `setup.py` without executing it.
+#[derive(Clone)]
+enum Literal {
+    Str(String, usize),
+    List(Vec<Literal>),
+    Dict(Vec<(String, Literal)>),
+}
+
+impl Literal {
+    /// The strings of a list literal, with the line each one is on.
+    fn strings(&self) -> Vec<(String, usize)> {
+        match self {
+            Literal::Str(s, line) => vec![(s.clone(), *line)],
+            Literal::List(items) => items.iter().flat_map(Literal::strings).collect(),
+            Literal::Dict(_) => Vec::new(),
+        }
+    }
+}
+
+/// Statically reads the keyword arguments of the `setup(...)` call in a `setup.py`.
+/// Module-level names assigned to literals (e.g. `REQUIRES = [...]`) are resolved.
+struct SetupPyCollector {
+    line_index: LineIndex,
+    names: HashMap<String, Literal>,
+    setuptools: SetuptoolsCollector,
+}
+
+impl SetupPyCollector {
+    fn line(&self, range: ast::text_size::TextRange) -> usize {
+        self.line_index.line_index(range.start()).to_usize()
+    }
+
+    fn literal(&self, expr: &ast::Expr) -> Option<Literal> {
+        match expr {
+            ast::Expr::Constant(ast::ExprConstant {
+                value: ast::Constant::Str(s),
+                range,
+                ..
+            }) => Some(Literal::Str(s.clone(), self.line(*range))),
+            ast::Expr::List(ast::ExprList { elts, .. })
+            | ast::Expr::Tuple(ast::ExprTuple { elts, .. }) => Some(Literal::List(
+                elts.iter().filter_map(|e| self.literal(e)).collect(),
+            )),
+            ast::Expr::Dict(ast::ExprDict { keys, values, .. }) => Some(Literal::Dict(
+                keys.iter()
+                    .zip(values)
+                    .filter_map(|(key, value)| match (key, self.literal(value)) {
+                        (Some(key), Some(value)) => match self.literal(key)? {
+                            Literal::Str(key, _) => Some((key, value)),
+                            _ => None,
+                        },
+                        _ => None,
+                    })
+                    .collect(),
+            )),
+            // e.g. `install_requires=BASE + EXTRA`
+            ast::Expr::BinOp(ast::ExprBinOp {
+                left,
+                op: ast::Operator::Add,
+                right,
+                ..
+            }) => match (self.literal(left)?, self.literal(right)?) {
+                (Literal::List(mut left), Literal::List(right)) => {
+                    left.extend(right);
+                    Some(Literal::List(left))
+                }
+                _ => None,
+            },
+            ast::Expr::Name(ast::ExprName { id, .. }) => self.names.get(id.as_str()).cloned(),
+            _ => None,
+        }
+    }
+
+    fn is_setup_call(func: &ast::Expr) -> bool {
+        match func {
+            ast::Expr::Name(ast::ExprName { id, .. }) => id.as_str() == "setup",
+            ast::Expr::Attribute(ast::ExprAttribute { attr, .. }) => attr.as_str() == "setup",
+            _ => false,
+        }
+    }
+}
+
+impl Visitor for SetupPyCollector {
+    fn visit_stmt_assign(&mut self, node: ast::StmtAssign) {
+        if let Some(value) = self.literal(&node.value) {
+            for target in &node.targets {
+                if let ast::Expr::Name(ast::ExprName { id, .. }) = target {
+                    self.names.insert(id.to_string(), value.clone());
+                }
+            }
+        }
+        self.generic_visit_stmt_assign(node);
+    }
+
+    fn visit_expr_call(&mut self, node: ast::ExprCall) {
+        if Self::is_setup_call(&node.func) {
+            for keyword in &node.keywords {
+                let Some(arg) = &keyword.arg else { continue };
+                let Some(value) = self.literal(&keyword.value) else {
+                    continue;
+                };
+                match (arg.as_str(), value) {
+                    ("install_requires", value) => {
+                        self.setuptools.visit_extra("", &value.strings())
+                    }
+                    ("extras_require", Literal::Dict(extras)) => {
+                        for (extra, requirements) in extras {
+                            self.setuptools.visit_extra(&extra, &requirements.strings());
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        self.generic_visit_expr_call(node);
+    }
+}
+
+/// Reads `install_requires` and `extras_require` from the `setup(...)` call of a `setup.py`.
+pub fn get_setup_py_dependencies(dep_spec_file: &Path) -> Result<HashSet<Dependency>> {
+    let content = fs::read_to_string(dep_spec_file)
+        .with_context(|| format!("Failed to read file at {:?}", dep_spec_file))?;
+
+    let ast = parse(&content, Mode::Module, "<embedded>")
+        .with_context(|| format!("Failed to parse {:?}", dep_spec_file))?;
+
+    let mut collector = SetupPyCollector {
+        line_index: LineIndex::from_source_text(&content),
+        names: HashMap::new(),
+        setuptools: SetuptoolsCollector::new(dep_spec_file),
+    };
+    ast.module()
+        .unwrap()
+        .body
+        .into_iter()
+        .for_each(|node| collector.visit_stmt(node));
+
+    Ok(collector.setuptools.dependencies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_assets::test_utils::find;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_setup_cfg() {
+        let options = parse_setup_cfg(
+            "[metadata]\nname = my_project\n\n[options]\n# comment\ninstall_requires =\n    requests>=2  # http client\n    ; rich\n    rich ; pretty output\npython_requires = >=3.8\n",
+        );
+
+        assert_eq!(
+            options[&("options".to_string(), "install_requires".to_string())],
+            vec![(7, "requests>=2".to_string()), (9, "rich".to_string())]
+        );
+        assert_eq!(
+            options[&("options".to_string(), "python_requires".to_string())],
+            vec![(10, ">=3.8".to_string())]
+        );
+        assert_eq!(
+            options[&("metadata".to_string(), "name".to_string())],
+            vec![(2, "my_project".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_cfg_list() {
+        assert_eq!(
+            cfg_list(&vec![(1, "requests; rich".to_string())]),
+            vec![("requests".to_string(), 1), ("rich".to_string(), 1)]
+        );
+        assert_eq!(
+            cfg_list(&vec![(2, "requests".to_string()), (3, "rich".to_string())]),
+            vec![("requests".to_string(), 2), ("rich".to_string(), 3)]
+        );
+    }
+
+    #[test]
+    fn parse_setup_cfg_dependencies() {
+        let temp_dir = tempdir().unwrap();
+        let setup_cfg = temp_dir.path().join("setup.cfg");
+        fs::write(
+            &setup_cfg,
+            "[options]
+packages = find:
+install_requires =
+    requests>=2.31  # http client
+    click
+
+[options.extras_require]
+test =
+    pytest
+docs = mkdocs; mkdocs-material
+",
+        )
+        .unwrap();
+
+        let dependencies =
+            get_setup_cfg_dependencies(&setup_cfg).expect("Failed to get dependencies");
+
+        assert_eq!(dependencies.len(), 5);
+        let requests = find(&dependencies, "requests");
+        assert_eq!(requests.version(), ">=2.31");
+        assert_eq!(requests.group(), MAIN_GROUP);
+        assert_eq!(requests.origin().unwrap().line(), Some(4));
+        assert!(!requests.is_optional());
+        assert_eq!(find(&dependencies, "pytest").group(), "test");
+        assert!(find(&dependencies, "pytest").is_optional());
+        assert_eq!(find(&dependencies, "mkdocs-material").group(), "docs");
+    }
+
+    #[test]
+    fn parse_setup_py_dependencies() {
+        let temp_dir = tempdir().unwrap();
+        let setup_py = temp_dir.path().join("setup.py");
+        fs::write(
+            &setup_py,
+            r#"from setuptools import setup, find_packages
+
+BASE = ["requests>=2.31", "click"]
+
+setup(
+    name="my_project",
+    packages=find_packages(),
+    install_requires=BASE + ["rich"],
+    extras_require={
+        "test": ["pytest"],
+        ":python_version < '3.8'": ["importlib-metadata"],
+    },
+)
+"#,
+        )
+        .unwrap();
+
+        let dependencies =
+            get_setup_py_dependencies(&setup_py).expect("Failed to get dependencies");
+
+        assert_eq!(dependencies.len(), 5);
+        let requests = find(&dependencies, "requests");
+        assert_eq!(requests.version(), ">=2.31");
+        assert_eq!(requests.origin().unwrap().line(), Some(3));
+        assert_eq!(find(&dependencies, "rich").group(), MAIN_GROUP);
+        assert_eq!(find(&dependencies, "pytest").group(), "test");
+
+        let importlib_metadata = find(&dependencies, "importlib-metadata");
+        assert_eq!(importlib_metadata.group(), MAIN_GROUP);
+        assert_eq!(importlib_metadata.marker(), Some("python_version < '3.8'"));
+    }
+
+    #[test]
+    fn test_invalid_setup_py() {
+        let temp_dir = tempdir().unwrap();
+        let setup_py = temp_dir.path().join("setup.py");
+        fs::write(&setup_py, "setup(").unwrap();
+
+        assert!(get_setup_py_dependencies(&setup_py).is_err());
+    }
+}
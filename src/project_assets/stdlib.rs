@@ -0,0 +1,547 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str;
+
+use serde::Serialize;
+
+use crate::project_assets::{Dependency, PackageName};
+
+/// The oldest and newest Python versions the module table knows about.
+const OLDEST_MINOR: u8 = 8;
+const NEWEST_MINOR: u8 = 13;
+
+/// Top-level modules of the standard library in every Python version from 3.8 to 3.13.
+const STDLIB_MODULES: &[&str] = &[
+    "__future__",
+    "__main__",
+    "_io",
+    "_thread",
+    "abc",
+    "antigravity",
+    "argparse",
+    "array",
+    "ast",
+    "asyncio",
+    "atexit",
+    "base64",
+    "bdb",
+    "binascii",
+    "bisect",
+    "builtins",
+    "bz2",
+    "cProfile",
+    "calendar",
+    "cmath",
+    "cmd",
+    "code",
+    "codecs",
+    "codeop",
+    "collections",
+    "colorsys",
+    "compileall",
+    "concurrent",
+    "configparser",
+    "contextlib",
+    "contextvars",
+    "copy",
+    "copyreg",
+    "csv",
+    "ctypes",
+    "curses",
+    "dataclasses",
+    "datetime",
+    "dbm",
+    "decimal",
+    "difflib",
+    "dis",
+    "doctest",
+    "email",
+    "encodings",
+    "ensurepip",
+    "enum",
+    "errno",
+    "faulthandler",
+    "fcntl",
+    "filecmp",
+    "fileinput",
+    "fnmatch",
+    "fractions",
+    "ftplib",
+    "functools",
+    "gc",
+    "genericpath",
+    "getopt",
+    "getpass",
+    "gettext",
+    "glob",
+    "grp",
+    "gzip",
+    "hashlib",
+    "heapq",
+    "hmac",
+    "html",
+    "http",
+    "idlelib",
+    "imaplib",
+    "importlib",
+    "inspect",
+    "io",
+    "ipaddress",
+    "itertools",
+    "json",
+    "keyword",
+    "linecache",
+    "locale",
+    "logging",
+    "lzma",
+    "mailbox",
+    "marshal",
+    "math",
+    "mimetypes",
+    "mmap",
+    "modulefinder",
+    "msvcrt",
+    "multiprocessing",
+    "netrc",
+    "nt",
+    "ntpath",
+    "nturl2path",
+    "numbers",
+    "opcode",
+    "operator",
+    "optparse",
+    "os",
+    "pathlib",
+    "pdb",
+    "pickle",
+    "pickletools",
+    "pkgutil",
+    "platform",
+    "plistlib",
+    "poplib",
+    "posix",
+    "posixpath",
+    "pprint",
+    "profile",
+    "pstats",
+    "pty",
+    "pwd",
+    "py_compile",
+    "pyclbr",
+    "pydoc",
+    "pydoc_data",
+    "pyexpat",
+    "queue",
+    "quopri",
+    "random",
+    "re",
+    "readline",
+    "reprlib",
+    "resource",
+    "rlcompleter",
+    "runpy",
+    "sched",
+    "secrets",
+    "select",
+    "selectors",
+    "shelve",
+    "shlex",
+    "shutil",
+    "signal",
+    "site",
+    "smtplib",
+    "socket",
+    "socketserver",
+    "sqlite3",
+    "sre_compile",
+    "sre_constants",
+    "sre_parse",
+    "ssl",
+    "stat",
+    "statistics",
+    "string",
+    "stringprep",
+    "struct",
+    "subprocess",
+    "symtable",
+    "sys",
+    "sysconfig",
+    "syslog",
+    "tabnanny",
+    "tarfile",
+    "tempfile",
+    "termios",
+    "textwrap",
+    "this",
+    "threading",
+    "time",
+    "timeit",
+    "tkinter",
+    "token",
+    "tokenize",
+    "trace",
+    "traceback",
+    "tracemalloc",
+    "tty",
+    "turtle",
+    "turtledemo",
+    "types",
+    "typing",
+    "unicodedata",
+    "unittest",
+    "urllib",
+    "uuid",
+    "venv",
+    "warnings",
+    "wave",
+    "weakref",
+    "webbrowser",
+    "winreg",
+    "winsound",
+    "wsgiref",
+    "xml",
+    "xmlrpc",
+    "zipapp",
+    "zipfile",
+    "zipimport",
+    "zlib",
+];
+
+/// Modules added (or removed) in a later Python version, with the minor version they were
+/// added in and the one they were removed in.
+const VERSIONED_STDLIB_MODULES: &[(&str, u8, Option<u8>)] = &[
+    ("dummy_threading", 8, Some(9)),
+    ("formatter", 8, Some(10)),
+    ("parser", 8, Some(10)),
+    ("symbol", 8, Some(10)),
+    ("binhex", 8, Some(11)),
+    ("asynchat", 8, Some(12)),
+    ("asyncore", 8, Some(12)),
+    ("distutils", 8, Some(12)),
+    ("imp", 8, Some(12)),
+    ("smtpd", 8, Some(12)),
+    ("aifc", 8, Some(13)),
+    ("audioop", 8, Some(13)),
+    ("cgi", 8, Some(13)),
+    ("cgitb", 8, Some(13)),
+    ("chunk", 8, Some(13)),
+    ("crypt", 8, Some(13)),
+    ("imghdr", 8, Some(13)),
+    ("lib2to3", 8, Some(13)),
+    ("mailcap", 8, Some(13)),
+    ("msilib", 8, Some(13)),
+    ("nis", 8, Some(13)),
+    ("nntplib", 8, Some(13)),
+    ("ossaudiodev", 8, Some(13)),
+    ("pipes", 8, Some(13)),
+    ("sndhdr", 8, Some(13)),
+    ("spwd", 8, Some(13)),
+    ("sunau", 8, Some(13)),
+    ("telnetlib", 8, Some(13)),
+    ("uu", 8, Some(13)),
+    ("xdrlib", 8, Some(13)),
+    ("graphlib", 9, None),
+    ("zoneinfo", 9, None),
+    ("tomllib", 11, None),
+];
+
+/// Distributions that backport a standard library module, with the module they provide and
+/// the minor version of Python 3 that ships it.
+const BACKPORTS: &[(&str, &str, u8)] = &[
+    ("argparse", "argparse", 2),
+    ("asyncio", "asyncio", 4),
+    ("backports-cached-property", "functools.cached_property", 8),
+    ("backports-zoneinfo", "zoneinfo", 9),
+    ("cached-property", "functools.cached_property", 8),
+    ("contextvars", "contextvars", 7),
+    ("dataclasses", "dataclasses", 7),
+    ("enum34", "enum", 4),
+    ("exceptiongroup", "builtins.ExceptionGroup", 11),
+    ("funcsigs", "inspect.signature", 3),
+    ("futures", "concurrent.futures", 2),
+    ("graphlib-backport", "graphlib", 9),
+    ("importlib-metadata", "importlib.metadata", 10),
+    ("importlib-resources", "importlib.resources", 9),
+    ("ipaddress", "ipaddress", 3),
+    ("mock", "unittest.mock", 3),
+    ("pathlib", "pathlib", 4),
+    ("pathlib2", "pathlib", 4),
+    ("selectors34", "selectors", 4),
+    ("singledispatch", "functools.singledispatch", 4),
+    ("statistics", "statistics", 4),
+    ("subprocess32", "subprocess", 3),
+    ("tomli", "tomllib", 11),
+    ("typing", "typing", 5),
+];
+
+/// A `major.minor` Python version.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(into = "String")]
+pub struct PythonVersion {
+    major: u8,
+    minor: u8,
+}
+
+impl PythonVersion {
+    pub fn new(major: u8, minor: u8) -> Self {
+        Self { major, minor }
+    }
+
+    /// Parses the leading `major.minor` of a version, e.g. `3.11.4` -> 3.11
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.trim().split('.');
+        let major = parts.next()?.trim().parse().ok()?;
+        let minor = parts
+            .next()
+            .map_or(Some(0), |minor| minor.trim().parse().ok())?;
+        Some(Self::new(major, minor))
+    }
+}
+
+impl fmt::Display for PythonVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl From<PythonVersion> for String {
+    fn from(version: PythonVersion) -> Self {
+        version.to_string()
+    }
+}
+
+/// Whether `module` is a top-level standard library module of the target Python.
+/// Without a target, a module of any known Python version counts.
+pub fn is_stdlib_module(module: &str, target: Option<PythonVersion>) -> bool {
+    if STDLIB_MODULES.contains(&module) {
+        return true;
+    }
+
+    // Versions outside of the table behave like the closest version it knows.
+    let minor = target
+        .filter(|target| target.major == 3)
+        .map(|target| target.minor.clamp(OLDEST_MINOR, NEWEST_MINOR));
+    VERSIONED_STDLIB_MODULES
+        .iter()
+        .any(|(name, added, removed)| {
+            *name == module
+                && minor.map_or(true, |minor| {
+                    *added <= minor && removed.map_or(true, |removed| minor < removed)
+                })
+        })
+}
+
+/// The lowest Python version allowed by a specifier such as `>=3.9`, `>=3.8,<4`, `^3.10`
+/// or `>=2.7,<2.8 || >=3.6`.
+pub fn lowest_python_version(specifier: &str) -> Option<PythonVersion> {
+    specifier
+        .split("||")
+        .filter_map(|alternative| {
+            alternative
+                .split(',')
+                .map(str::trim)
+                .filter(|clause| !clause.starts_with('<') && !clause.starts_with("!="))
+                .filter_map(|clause| {
+                    PythonVersion::parse(clause.trim_start_matches(['>', '=', '~', '^', ' ']))
+                })
+                .max()
+        })
+        .min()
+}
+
+/// Reads `[project] requires-python` or the `python` dependency of Poetry from a `pyproject.toml`.
+fn get_requires_python(pyproject: &Path) -> Option<PythonVersion> {
+    let toml_str = fs::read_to_string(pyproject).ok()?;
+    let toml_value: toml::Value = toml::from_str(&toml_str).ok()?;
+
+    let requires_python = toml_value
+        .get("project")
+        .and_then(|project| project.get("requires-python"))
+        .or_else(|| {
+            toml_value
+                .get("tool")
+                .and_then(|tool| tool.get("poetry"))
+                .and_then(|poetry| poetry.get("dependencies"))
+                .and_then(|dependencies| dependencies.get("python"))
+        })?;
+    lowest_python_version(requires_python.as_str()?)
+}
+
+/// Executes `python --version` to get the version of the active interpreter.
+fn get_interpreter_version() -> Option<PythonVersion> {
+    let output = Command::new("python").arg("--version").output().ok()?;
+    // Python 2 prints its version to stderr.
+    let output = [output.stdout, output.stderr].concat();
+    let version = str::from_utf8(&output)
+        .ok()?
+        .trim()
+        .strip_prefix("Python ")?;
+    PythonVersion::parse(version)
+}
+
+/// The Python version the project targets: the lowest version allowed by its `pyproject.toml`,
+/// or else the version of the active interpreter.
+pub fn get_target_python(dep_spec_files: &[PathBuf]) -> Option<PythonVersion> {
+    dep_spec_files
+        .iter()
+        .filter(|file| file.ends_with("pyproject.toml"))
+        .find_map(|pyproject| get_requires_python(pyproject))
+        .or_else(get_interpreter_version)
+}
+
+/// A declared backport of a module the target Python already provides.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct Backport {
+    id: PackageName,
+    group: String,
+    /// The standard library module it backports.
+    module: &'static str,
+    /// The first Python version that provides the module.
+    since: PythonVersion,
+}
+
+impl Backport {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    pub fn module(&self) -> &str {
+        self.module
+    }
+
+    pub fn since(&self) -> PythonVersion {
+        self.since
+    }
+}
+
+/// Finds the declared dependencies that backport a module the target Python already provides.
+/// Dependencies restricted to older Pythons by an environment marker are needed, so they are kept.
+pub fn find_backports<'a>(
+    dependencies: impl IntoIterator<Item = &'a Dependency>,
+    target: PythonVersion,
+) -> Vec<Backport> {
+    let mut backports: Vec<Backport> = dependencies
+        .into_iter()
+        .filter(|dep| {
+            !dep.marker()
+                .is_some_and(|marker| marker.contains("python_"))
+        })
+        .filter_map(|dep| {
+            let (_, module, minor) = BACKPORTS.iter().find(|(name, ..)| dep.name() == *name)?;
+            let since = PythonVersion::new(3, *minor);
+            (since <= target).then(|| Backport {
+                id: dep.name().clone(),
+                group: dep.group().to_string(),
+                module,
+                since,
+            })
+        })
+        .collect();
+    backports.sort_by(|a, b| (&a.id, &a.group).cmp(&(&b.id, &b.group)));
+    backports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    use crate::project_assets::DependencyBuilder;
+
+    #[test]
+    fn test_is_stdlib_module() {
+        let py38 = Some(PythonVersion::new(3, 8));
+        let py313 = Some(PythonVersion::new(3, 13));
+
+        assert!(is_stdlib_module("os", py38));
+        assert!(is_stdlib_module("__future__", py313));
+        assert!(is_stdlib_module("_thread", py38));
+        assert!(!is_stdlib_module("requests", None));
+
+        assert!(!is_stdlib_module("tomllib", py38));
+        assert!(is_stdlib_module("tomllib", Some(PythonVersion::new(3, 11))));
+        assert!(is_stdlib_module(
+            "distutils",
+            Some(PythonVersion::new(3, 11))
+        ));
+        assert!(!is_stdlib_module(
+            "distutils",
+            Some(PythonVersion::new(3, 12))
+        ));
+        assert!(!is_stdlib_module("imghdr", py313));
+        // Versions outside of the table use the closest known one.
+        assert!(!is_stdlib_module("imghdr", Some(PythonVersion::new(3, 14))));
+        // Without a target, any known version counts.
+        assert!(is_stdlib_module("tomllib", None));
+        assert!(is_stdlib_module("distutils", None));
+    }
+
+    #[test]
+    fn test_lowest_python_version() {
+        let version = |major, minor| Some(PythonVersion::new(major, minor));
+        assert_eq!(lowest_python_version(">=3.9"), version(3, 9));
+        assert_eq!(lowest_python_version(">=3.8, <4"), version(3, 8));
+        assert_eq!(lowest_python_version("^3.10"), version(3, 10));
+        assert_eq!(lowest_python_version("~=3.11.2"), version(3, 11));
+        assert_eq!(lowest_python_version("==3.12.*"), version(3, 12));
+        assert_eq!(lowest_python_version(">=2.7,<2.8 || >=3.6"), version(2, 7));
+        assert_eq!(lowest_python_version("<3.13"), None);
+        assert_eq!(lowest_python_version("*"), None);
+    }
+
+    #[test]
+    fn test_requires_python() {
+        let temp_dir = tempdir().unwrap();
+        let pyproject = temp_dir.path().join("pyproject.toml");
+
+        fs::write(&pyproject, "[project]\nrequires-python = \">=3.10\"\n").unwrap();
+        assert_eq!(
+            get_requires_python(&pyproject),
+            Some(PythonVersion::new(3, 10))
+        );
+
+        fs::write(
+            &pyproject,
+            "[tool.poetry.dependencies]\npython = \"^3.9\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            get_requires_python(&pyproject),
+            Some(PythonVersion::new(3, 9))
+        );
+
+        fs::write(&pyproject, "[project]\nname = \"my_project\"\n").unwrap();
+        assert_eq!(get_requires_python(&pyproject), None);
+    }
+
+    #[test]
+    fn test_find_backports() {
+        let dependencies = [
+            DependencyBuilder::new("tomli".to_string()).build(),
+            DependencyBuilder::new("dataclasses".to_string())
+                .group("dev".to_string())
+                .build(),
+            DependencyBuilder::new("importlib_metadata".to_string())
+                .marker("python_version < \"3.10\"".to_string())
+                .build(),
+            DependencyBuilder::new("requests".to_string()).build(),
+        ];
+
+        let backports = find_backports(&dependencies, PythonVersion::new(3, 10));
+        assert_eq!(backports.len(), 1);
+        assert_eq!(backports[0].id(), "dataclasses");
+        assert_eq!(backports[0].group(), "dev");
+        assert_eq!(backports[0].module(), "dataclasses");
+        assert_eq!(backports[0].since().to_string(), "3.7");
+
+        let backports = find_backports(&dependencies, PythonVersion::new(3, 12));
+        let ids: Vec<&str> = backports.iter().map(Backport::id).collect();
+        assert_eq!(ids, vec!["dataclasses", "tomli"]);
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cli::{Env, OutputKind};
+use crate::config::Config;
+use crate::project_assets::{Dependency, PackageState};
+
+/// Helper function to create a Config struct for testing, which scans `base_directory` with the
+/// default options.
+pub fn te_config(base_directory: PathBuf) -> Config {
+    Config {
+        base_directory,
+        ignore_hidden: true,
+        max_depth: None,
+        package_state: PackageState::Unused,
+        dep_spec_files: Vec::new(),
+        lock_file: None,
+        env: Env::Test,
+        output: OutputKind::Human,
+        groups: Vec::new(),
+        strict: false,
+        cache: false,
+        include: Vec::new(),
+        exclude: Vec::new(),
+        gitignore: true,
+        global_gitignore: true,
+        ignore_files: true,
+        test_globs: Vec::new(),
+    }
+}
+
+/// Helper function to create a file, and its parent directories, in the temporary directory.
+/// The content is written with a trailing newline.
+pub fn create_file(dir: &tempfile::TempDir, path: &str, content: &str) -> PathBuf {
+    let file_path = dir.path().join(path);
+    fs::create_dir_all(file_path.parent().unwrap()).expect("Failed to create directories.");
+    fs::write(&file_path, format!("{}\n", content)).expect("Failed to write to file.");
+    file_path
+}
+
+/// Helper function to find a dependency by id.
+pub fn find<'a>(dependencies: &'a HashSet<Dependency>, id: &str) -> &'a Dependency {
+    dependencies
+        .iter()
+        .find(|dep| dep.id() == id)
+        .expect("Dependency not found")
+}
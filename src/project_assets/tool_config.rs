@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// The settings of unpack in the `[tool.unpack]` table of a `pyproject.toml`.
+/// Command line options are added to, or override, them.
+#[derive(Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ToolConfig {
+    /// Globs of the only files to scan for imports, e.g. `src/**`
+    pub include: Vec<String>,
+    /// Globs of the files and directories not to scan, e.g. `migrations/`
+    pub exclude: Vec<String>,
+    /// Whether to respect `.gitignore` files.
+    pub gitignore: Option<bool>,
+    /// Whether to respect the global gitignore file of git, e.g. `~/.config/git/ignore`
+    pub global_gitignore: Option<bool>,
+    /// Whether to respect `.ignore` files.
+    pub ignore_files: Option<bool>,
+    /// Globs of the test files, on top of the default ones, e.g. `integration/`
+    pub test_globs: Vec<String>,
+}
+
+/// Reads the `[tool.unpack]` table of the `pyproject.toml` at the root of the project, if any.
+pub fn get_tool_config(base_directory: &Path) -> Result<ToolConfig> {
+    let pyproject = base_directory.join("pyproject.toml");
+    if !pyproject.is_file() {
+        return Ok(ToolConfig::default());
+    }
+
+    let toml_str = fs::read_to_string(&pyproject)
+        .with_context(|| format!("Failed to read TOML file at {:?}", pyproject))?;
+    let toml_value: toml::Value =
+        toml::from_str(&toml_str).with_context(|| "Failed to parse TOML content")?;
+
+    match toml_value.get("tool").and_then(|tool| tool.get("unpack")) {
+        Some(table) => table
+            .clone()
+            .try_into()
+            .with_context(|| format!("Invalid `[tool.unpack]` table in {:?}", pyproject)),
+        None => Ok(ToolConfig::default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_get_tool_config() {
+        let temp_dir = tempdir().unwrap();
+        assert_eq!(
+            get_tool_config(temp_dir.path()).unwrap(),
+            ToolConfig::default()
+        );
+
+        let pyproject = temp_dir.path().join("pyproject.toml");
+        fs::write(
+            &pyproject,
+            r#"
+[tool.unpack]
+exclude = ["migrations/", "vendor/"]
+gitignore = false
+"#,
+        )
+        .unwrap();
+        let tool_config = get_tool_config(temp_dir.path()).unwrap();
+        assert_eq!(tool_config.exclude, vec!["migrations/", "vendor/"]);
+        assert!(tool_config.include.is_empty());
+        assert_eq!(tool_config.gitignore, Some(false));
+        assert_eq!(tool_config.global_gitignore, None);
+
+        // A misspelled setting is an error rather than silently ignored.
+        fs::write(&pyproject, "[tool.unpack]\nexclued = [\"build/\"]\n").unwrap();
+        assert!(get_tool_config(temp_dir.path()).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::WalkBuilder;
+
+use crate::config::Config;
+
+/// The include and exclude globs, matched like `.gitignore` lines from the root of the project.
+/// With include globs, the files that match none of them are skipped.
+fn overrides(config: &Config, include: &[String]) -> Result<Override> {
+    let mut builder = OverrideBuilder::new(&config.base_directory);
+    for glob in include {
+        builder
+            .add(glob)
+            .with_context(|| format!("Invalid include glob `{}`", glob))?;
+    }
+    for glob in &config.exclude {
+        builder
+            .add(&format!("!{}", glob))
+            .with_context(|| format!("Invalid exclude glob `{}`", glob))?;
+    }
+    builder
+        .build()
+        .context("Failed to build the include and exclude globs")
+}
+
+/// Builds a walker of the project, honouring the hidden, depth and ignore file settings.
+/// Only the files matching `include` are walked, if any, and never the excluded ones.
+pub fn project_walker(config: &Config, include: &[String]) -> Result<WalkBuilder> {
+    let mut builder = WalkBuilder::new(&config.base_directory);
+    builder
+        .hidden(config.ignore_hidden)
+        .max_depth(config.max_depth)
+        .git_ignore(config.gitignore)
+        .git_exclude(config.gitignore)
+        .git_global(config.global_gitignore)
+        .ignore(config.ignore_files)
+        .overrides(overrides(config, include)?);
+    Ok(builder)
+}
+
+/// Tells the test files of the project from its production code, by globs matched like the lines
+/// of a `.gitignore`, e.g. `tests/` or `test_*.py`
+pub struct TestFiles(Gitignore);
+
+impl TestFiles {
+    pub fn new(base_directory: &Path, globs: &[String]) -> Result<Self> {
+        let mut builder = GitignoreBuilder::new(base_directory);
+        for glob in globs {
+            builder
+                .add_line(None, glob)
+                .with_context(|| format!("Invalid test glob `{}`", glob))?;
+        }
+        let matcher = builder.build().context("Failed to build the test globs")?;
+        Ok(Self(matcher))
+    }
+
+    /// Whether the file is a test file, or is in a test directory.
+    pub fn contains(&self, file: &Path) -> bool {
+        file.ancestors()
+            .enumerate()
+            .any(|(idx, path)| self.0.matched(path, idx > 0).is_ignore())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_test_files() {
+        let globs = ["tests/", "test_*.py", "conftest.py"].map(String::from);
+        let tests = TestFiles::new(Path::new("."), &globs).unwrap();
+
+        assert!(tests.contains(Path::new("./tests/unit/test_app.py")));
+        assert!(tests.contains(Path::new("./tests/helpers.py")));
+        assert!(tests.contains(Path::new("./src/app/test_views.py")));
+        assert!(tests.contains(Path::new("./conftest.py")));
+        assert!(!tests.contains(Path::new("./src/app/views.py")));
+        assert!(!tests.contains(Path::new("./src/testing.py")));
+    }
+}